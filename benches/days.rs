@@ -0,0 +1,117 @@
+//! Benchmarks the generator and both parts of every day.
+//!
+//! Each day is measured against every input file named `<year>/dayN.txt` or
+//! `<year>/dayN-<label>.txt` in the input directories, which default to
+//! `input` and can be replaced by setting `ADVENT_BENCH_INPUTS` to a list of
+//! directories separated like `PATH`. Benchmarks are named
+//! `<year>/dayN/<stage>/<input>`, so `cargo bench -- 2020/day15/part2` runs
+//! just one.
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use advent_2020::registry::{self, Solver};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, SamplingMode};
+
+/// A single run longer than this switches the day to a handful of flat
+/// samples, so that the heavy parts finish in reasonable time.
+const SLOW: Duration = Duration::from_millis(50);
+
+fn input_dirs() -> Vec<PathBuf> {
+    match env::var_os("ADVENT_BENCH_INPUTS") {
+        Some(dirs) => env::split_paths(&dirs).collect(),
+        None => vec![PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("input")],
+    }
+}
+
+/// Finds the inputs for `day` of `year` under `dir`, labelled by their file
+/// stem.
+fn inputs(dir: &Path, year: u16, day: u8) -> Vec<(String, String)> {
+    let name = format!("day{}", day);
+    let mut inputs = fs::read_dir(dir.join(year.to_string()))
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .filter(|path| {
+            let stem = path.file_stem().and_then(|stem| stem.to_str());
+            stem.is_some_and(|stem| stem == name || stem.starts_with(&format!("{}-", name)))
+        })
+        .filter_map(|path| {
+            let label = path.file_stem()?.to_string_lossy().into_owned();
+            let input = fs::read_to_string(&path).ok()?;
+            Some((label, input))
+        })
+        .collect::<Vec<_>>();
+
+    inputs.sort();
+    inputs
+}
+
+fn bench_day(c: &mut Criterion, solver: &dyn Solver) {
+    let inputs = input_dirs()
+        .iter()
+        .flat_map(|dir| inputs(dir, solver.year(), solver.day()))
+        .collect::<Vec<_>>();
+    if inputs.is_empty() {
+        eprintln!(
+            "no input for {} day {}, skipping",
+            solver.year(),
+            solver.day()
+        );
+        return;
+    }
+
+    let mut group = c.benchmark_group(format!("{}/day{}", solver.year(), solver.day()));
+    for (label, input) in &inputs {
+        let parsed = match solver.parse(input) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                eprintln!("skipping {}: {}", label, e);
+                continue;
+            }
+        };
+
+        let slowest = solver
+            .parts()
+            .iter()
+            .map(|&part| {
+                let start = Instant::now();
+                solver.solve(&parsed, part);
+                start.elapsed()
+            })
+            .max()
+            .unwrap_or_default();
+        if slowest > SLOW {
+            group.sample_size(10).sampling_mode(SamplingMode::Flat);
+        } else {
+            group.sample_size(100).sampling_mode(SamplingMode::Auto);
+        }
+
+        group.bench_with_input(BenchmarkId::new("generator", label), input, |b, input| {
+            b.iter(|| solver.parse(black_box(input)))
+        });
+        for &part in solver.parts() {
+            let id = BenchmarkId::new(format!("part{}", part), label);
+            group.bench_with_input(id, &parsed, |b, parsed| {
+                b.iter(|| solver.solve(black_box(parsed), part))
+            });
+        }
+    }
+
+    group.finish();
+}
+
+fn days(c: &mut Criterion) {
+    for year in registry::years() {
+        for &solver in registry::solvers(year) {
+            bench_day(c, solver);
+        }
+    }
+}
+
+criterion_group!(benches, days);
+criterion_main!(benches);
@@ -1,28 +1,511 @@
-use itertools::Itertools;
+use std::{
+    cmp::Ordering, collections::VecDeque, error::Error, fmt, io::BufRead, num::ParseIntError,
+    str::FromStr,
+};
 
-const SEARCH_VALUE: u32 = 2020;
+use crate::{
+    parse::{self, ParseError},
+    trace::event,
+    Puzzle,
+};
+
+const DAY: u8 = 1;
+const SEARCH_VALUE: i64 = 2020;
+
+/// An integer type that expense report entries can be kept in. Signed
+/// types allow refunds, and all arithmetic on entries is checked.
+pub trait Amount:
+    Copy + Ord + fmt::Debug + fmt::Display + FromStr<Err = ParseIntError> + Send + Sync + 'static
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! amounts {
+    ($($t:ty),*) => {
+        $(
+            impl Amount for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn checked_sub(self, other: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, other)
+                }
+
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, other)
+                }
+            }
+        )*
+    };
+}
+
+amounts!(i32, i64, i128, u32, u64, u128);
+
+/// Parses an expense report with one entry per line.
+pub fn parse_report<T: Amount>(input: &str) -> Result<Vec<T>, ParseError> {
+    let input = &parse::normalize(input);
+    parse::lines(input, |line| parse::field(DAY, line, line))
+}
+
+/// Parses an expense report as it is read, see [`parse_report`].
+pub fn read_report<T: Amount, R: BufRead>(reader: R) -> Result<Vec<T>, ParseError> {
+    parse::read_lines(DAY, reader, |line| parse::field(DAY, line, line))
+}
 
 #[aoc_generator(day1)]
-fn generator(input: &str) -> Vec<u32> {
-    input.lines().map(str::parse).map(Result::unwrap).collect()
+fn generator(input: &str) -> Result<Vec<i64>, ParseError> {
+    parse_report(input)
+}
+
+/// Why an expense report has no answer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExpenseError<T> {
+    /// No `k` different entries add up to the target.
+    NoMatch { k: usize, target: T },
+    /// The matching entries multiply to more than fits in the entry type.
+    Overflow,
+}
+
+impl<T: fmt::Display> fmt::Display for ExpenseError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExpenseError::NoMatch { k, target } => {
+                write!(f, "no {} entries add up to {}", k, target)
+            }
+            ExpenseError::Overflow => write!(f, "the product of the entries overflows"),
+        }
+    }
+}
+
+impl<T: fmt::Debug + fmt::Display> Error for ExpenseError<T> {}
+
+/// The answer to either part, which shows the product or why there is
+/// none.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Answer(pub Result<i64, ExpenseError<i64>>);
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Ok(product) => write!(f, "{}", product),
+            Err(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Every way of picking `k` different entries that add up to a target, as
+/// the indices of the entries in increasing order. Entries with equal
+/// values are still different entries, so each combination of indices is
+/// produced exactly once, but no index appears twice within one.
+///
+/// The entries are sorted once, then every choice of the first `k - 2` is
+/// tried and the last two are found with a two pointer scan, so the search
+/// takes O(n^(k-1)) time for `k` of two or more, plus the time to produce
+/// each match. Sums are never formed, only checked differences from the
+/// target, so a combination is found as long as what is left of the target
+/// after each of its first `k - 2` entries fits in `T`.
+#[derive(Debug, Clone)]
+pub struct Sums<T> {
+    order: Vec<usize>,
+    sorted: Vec<T>,
+    k: usize,
+    target: T,
+    started: bool,
+    /// Positions in `sorted` picked for all but the last two entries.
+    prefix: Vec<usize>,
+    /// What is left of the target before each pick and after the last.
+    rests: Vec<T>,
+    /// The two pointers scanning for the last two entries.
+    low: usize,
+    high: usize,
+    /// Matches found by the scan that have not been produced yet.
+    pending: VecDeque<(usize, usize)>,
+}
+
+pub fn sums<T: Amount>(entries: &[T], k: usize, target: T) -> Sums<T> {
+    let mut order = (0..entries.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| entries[i]);
+    let sorted = order.iter().map(|&i| entries[i]).collect();
+
+    let mut rests = Vec::with_capacity(k + 1);
+    rests.push(target);
+    Sums {
+        order,
+        sorted,
+        k,
+        target,
+        started: false,
+        prefix: Vec::with_capacity(k),
+        rests,
+        low: 0,
+        high: 0,
+        pending: VecDeque::new(),
+    }
+}
+
+impl<T: Amount> Sums<T> {
+    fn rest(&self) -> T {
+        *self.rests.last().unwrap()
+    }
+
+    /// Moves on to the next prefix whose entries leave something of the
+    /// target that fits in `T`, and restarts the scan after it. Returns
+    /// false once all have been tried.
+    fn next_prefix(&mut self) -> bool {
+        let depth = self.k - 2;
+        let mut next = if self.started {
+            match self.pop() {
+                Some(last) => last + 1,
+                None => return false,
+            }
+        } else {
+            self.started = true;
+            0
+        };
+
+        while self.prefix.len() < depth {
+            let rest = self.rest();
+            let value = self.sorted.get(next).copied();
+            match value {
+                // Every later entry is at least as large, and none of them
+                // is negative, so they would all overshoot.
+                Some(value) if value >= T::ZERO && value > rest => {}
+                Some(value) => {
+                    if let Some(left) = rest.checked_sub(value) {
+                        self.prefix.push(next);
+                        self.rests.push(left);
+                    }
+                    next += 1;
+                    continue;
+                }
+                None => {}
+            }
+
+            match self.pop() {
+                Some(last) => next = last + 1,
+                None => return false,
+            }
+        }
+
+        self.low = next;
+        self.high = self.sorted.len().saturating_sub(1);
+        true
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        let last = self.prefix.pop()?;
+        self.rests.pop();
+        Some(last)
+    }
+
+    /// Scans for the next pair that completes the prefix, queueing every
+    /// pair of positions with those two values. Returns false once the scan
+    /// is over.
+    fn scan(&mut self) -> bool {
+        let rest = self.rest();
+        let sorted = &self.sorted;
+        while self.low < self.high {
+            let (low, high) = (self.low, self.high);
+            let order = match rest.checked_sub(sorted[low]) {
+                Some(partner) => sorted[high].cmp(&partner),
+                // The partner would be past the top of `T` when a negative
+                // entry is taken away, and past the bottom otherwise.
+                None if sorted[low] < T::ZERO => Ordering::Less,
+                None => Ordering::Greater,
+            };
+            match order {
+                Ordering::Less => self.low += 1,
+                Ordering::Greater => self.high -= 1,
+                Ordering::Equal if sorted[low] == sorted[high] => {
+                    for i in low..high {
+                        self.pending.extend((i + 1..=high).map(|j| (i, j)));
+                    }
+                    self.low = high;
+                    return true;
+                }
+                Ordering::Equal => {
+                    let low_end = (low..high)
+                        .find(|&i| sorted[i] != sorted[low])
+                        .unwrap_or(high);
+                    let high_start = (low_end..=high)
+                        .rev()
+                        .find(|&j| sorted[j] != sorted[high])
+                        .map_or(low_end, |j| j + 1);
+                    for i in low..low_end {
+                        self.pending.extend((high_start..=high).map(|j| (i, j)));
+                    }
+                    self.low = low_end;
+                    self.high = high_start - 1;
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    fn indices(&self, positions: impl Iterator<Item = usize>) -> Vec<usize> {
+        let mut indices = positions.map(|p| self.order[p]).collect::<Vec<_>>();
+        indices.sort_unstable();
+        indices
+    }
+}
+
+impl<T: Amount> Iterator for Sums<T> {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.k {
+            0 => {
+                let first = !self.started;
+                self.started = true;
+                (first && self.target == T::ZERO).then(Vec::new)
+            }
+            1 => {
+                while self.low < self.sorted.len() && self.sorted[self.low] <= self.target {
+                    self.low += 1;
+                    if self.sorted[self.low - 1] == self.target {
+                        return Some(vec![self.order[self.low - 1]]);
+                    }
+                }
+                None
+            }
+            _ => loop {
+                if let Some((low, high)) = self.pending.pop_front() {
+                    let positions = self.prefix.iter().copied().chain(vec![low, high]);
+                    return Some(self.indices(positions));
+                }
+                if !self.scan() && !self.next_prefix() {
+                    return None;
+                }
+            },
+        }
+    }
 }
 
-fn solve(size: usize, input: &[u32]) -> u32 {
-    let entries = input
+/// Finds `k` different entries that add up to `target` and returns their
+/// indices in increasing order, or `None` if no such entries exist. See
+/// [`sums`] for how the search goes.
+pub fn find_sum<T: Amount>(entries: &[T], k: usize, target: T) -> Option<Vec<usize>> {
+    sums(entries, k, target).next()
+}
+
+/// Multiplies together the entries at `indices`, or returns `None` if the
+/// product does not fit in `T`.
+pub fn product<T: Amount>(entries: &[T], indices: &[usize]) -> Option<T> {
+    indices
         .iter()
-        .combinations(size)
-        .find(|entries| entries.iter().copied().sum::<u32>() == SEARCH_VALUE)
-        .unwrap();
+        .try_fold(T::ONE, |product, &i| product.checked_mul(entries[i]))
+}
 
-    entries.iter().copied().product()
+/// The product of the first `k` entries found that add up to `target`.
+pub fn solve<T: Amount>(entries: &[T], k: usize, target: T) -> Result<T, ExpenseError<T>> {
+    let indices = find_sum(entries, k, target).ok_or(ExpenseError::NoMatch { k, target })?;
+    event!(
+        DAY,
+        "match",
+        indices = format!("{:?}", indices),
+        entries = format!(
+            "{:?}",
+            indices.iter().map(|&i| entries[i]).collect::<Vec<_>>()
+        ),
+    );
+    product(entries, &indices).ok_or(ExpenseError::Overflow)
 }
 
 #[aoc(day1, part1)]
-fn part1(input: &[u32]) -> u32 {
-    solve(2, &input)
+fn part1(input: &[i64]) -> Result<i64, ExpenseError<i64>> {
+    solve(input, 2, SEARCH_VALUE)
 }
 
 #[aoc(day1, part2)]
-fn part2(input: &[u32]) -> u32 {
-    solve(3, &input)
+fn part2(input: &[i64]) -> Result<i64, ExpenseError<i64>> {
+    solve(input, 3, SEARCH_VALUE)
+}
+
+pub struct Day1;
+
+impl Puzzle for Day1 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = DAY;
+
+    type Input = Vec<i64>;
+    type Part1 = Answer;
+    type Part2 = Answer;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        generator(input)
+    }
+
+    fn parse_reader<R: BufRead>(reader: R) -> Result<Self::Input, ParseError> {
+        read_report(reader)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        Answer(part1(input))
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        Answer(part2(input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "1721\n979\n366\n299\n675\n1456";
+
+    #[test]
+    fn part1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), Ok(514579));
+    }
+
+    #[test]
+    fn part2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part2(&input), Ok(241861950));
+    }
+
+    #[test]
+    fn find_sum_returns_indices() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(find_sum(&input, 2, 2020), Some(vec![0, 3]));
+        assert_eq!(find_sum(&input, 3, 2020), Some(vec![1, 2, 4]));
+        assert_eq!(find_sum(&input, 1, 979), Some(vec![1]));
+        assert_eq!(
+            find_sum(&input, 4, 1721 + 979 + 366 + 299),
+            Some(vec![0, 1, 2, 3])
+        );
+        assert_eq!(find_sum(&input, 0, 0), Some(vec![]));
+        assert_eq!(find_sum(&input, 2, 1), None);
+        assert_eq!(find_sum(&input, 7, 2020), None);
+    }
+
+    #[test]
+    fn find_sum_uses_each_entry_once() {
+        assert_eq!(find_sum(&[1010], 2, 2020), None);
+        assert_eq!(find_sum(&[5, 1010, 7, 1010], 2, 2020), Some(vec![1, 3]));
+        assert_eq!(find_sum(&[1, 1, 1, 2], 3, 3), Some(vec![0, 1, 2]));
+        assert_eq!(find_sum(&[u32::MAX, u32::MAX, 1], 2, u32::MAX), None);
+    }
+
+    #[test]
+    fn sums_finds_every_combination() {
+        let all = |entries: &[u32], k, target| {
+            let mut found = sums(entries, k, target).collect::<Vec<_>>();
+            found.sort();
+            found
+        };
+
+        assert_eq!(
+            all(&[1010, 1010, 1010], 2, 2020),
+            [vec![0, 1], vec![0, 2], vec![1, 2]]
+        );
+        assert_eq!(
+            all(&[3, 1, 2, 3, 1, 2], 2, 4),
+            [vec![0, 1], vec![0, 4], vec![1, 3], vec![2, 5], vec![3, 4]]
+        );
+        assert_eq!(all(&[1, 2, 3, 4, 5], 3, 9), [vec![0, 2, 4], vec![1, 2, 3]]);
+        assert_eq!(all(&[2, 1, 2], 1, 2), [vec![0], vec![2]]);
+        assert_eq!(all(&[2, 1, 2], 0, 0), [Vec::<usize>::new()]);
+        assert!(all(&[1, 2], 3, 3).is_empty());
+    }
+
+    #[test]
+    fn sums_matches_brute_force() {
+        let entries = [4, 1, 3, 3, 0, 2, 1, 4, 2, 0, 3];
+        for k in 0..=5 {
+            for target in 0..=16 {
+                let mut expected = Vec::new();
+                for mask in 0u32..1 << entries.len() {
+                    let indices = (0..entries.len())
+                        .filter(|&i| mask & (1 << i) != 0)
+                        .collect::<Vec<_>>();
+                    let sum = indices.iter().map(|&i| entries[i]).sum::<u32>();
+                    if indices.len() == k && sum == target {
+                        expected.push(indices);
+                    }
+                }
+                expected.sort();
+
+                let mut found = sums(&entries, k, target).collect::<Vec<_>>();
+                found.sort();
+                assert_eq!(found, expected, "k = {}, target = {}", k, target);
+            }
+        }
+    }
+
+    #[test]
+    fn refunds_are_negative_entries() {
+        let entries = parse_report::<i64>("1500\n-480\n3000\n-980\n1000").unwrap();
+        assert_eq!(entries, [1500, -480, 3000, -980, 1000]);
+        assert_eq!(find_sum(&entries, 2, 2020), Some(vec![2, 3]));
+        assert_eq!(solve(&entries, 2, 2020), Ok(-2_940_000));
+        assert_eq!(find_sum(&entries, 3, 2020), Some(vec![0, 1, 4]));
+        assert!(parse_report::<u64>("1500\n-480").is_err());
+    }
+
+    #[test]
+    fn sums_never_overflow() {
+        assert_eq!(find_sum(&[i64::MIN, i64::MAX], 2, -1), Some(vec![0, 1]));
+        assert_eq!(find_sum(&[i64::MIN, i64::MIN, 5], 2, 0), None);
+        assert_eq!(
+            find_sum(&[i64::MAX, -1, 1, i64::MIN], 3, -2),
+            Some(vec![0, 1, 3])
+        );
+        assert_eq!(find_sum(&[u64::MAX, 1, 0], 2, u64::MAX), Some(vec![0, 2]));
+        assert_eq!(
+            find_sum(&[i128::MAX, i128::MAX, i128::MIN, 2], 3, i128::MAX),
+            None
+        );
+        assert_eq!(
+            solve(&[i32::MIN, i32::MAX], 2, -1),
+            Err(ExpenseError::Overflow)
+        );
+    }
+
+    #[test]
+    fn signed_sums_match_brute_force() {
+        let entries = [4i64, -1, 3, -3, 0, 2, -1, 4, -2, 0, 3];
+        for k in 0..=4 {
+            for target in -8..=8 {
+                let mut expected = Vec::new();
+                for mask in 0u32..1 << entries.len() {
+                    let indices = (0..entries.len())
+                        .filter(|&i| mask & (1 << i) != 0)
+                        .collect::<Vec<_>>();
+                    let sum = indices.iter().map(|&i| entries[i]).sum::<i64>();
+                    if indices.len() == k && sum == target {
+                        expected.push(indices);
+                    }
+                }
+                expected.sort();
+
+                let mut found = sums(&entries, k, target).collect::<Vec<_>>();
+                found.sort();
+                assert_eq!(found, expected, "k = {}, target = {}", k, target);
+            }
+        }
+    }
+
+    #[test]
+    fn answers_report_failures() {
+        assert_eq!(product(&[70_000, 70_000], &[0, 1]), None);
+        assert_eq!(product(&[2, 3, 7], &[0, 2]), Some(14));
+
+        assert_eq!(
+            part1(&[1, 2]),
+            Err(ExpenseError::NoMatch { k: 2, target: 2020 })
+        );
+        assert_eq!(part2(&[2000, 10, 10]), Ok(200_000));
+        assert_eq!(
+            Day1::part1(&vec![1, 2]).to_string(),
+            "no 2 entries add up to 2020"
+        );
+    }
 }
@@ -1,15 +1,25 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, io::BufRead};
 
 use itertools::Itertools;
 
+use crate::{
+    parse::{self, ParseError},
+    trace::event,
+    Puzzle,
+};
+
+const DAY: u8 = 10;
+
 #[aoc_generator(day10)]
-fn generator(input: &str) -> Vec<usize> {
-    input
-        .lines()
-        .map(str::parse)
-        .map(Result::unwrap)
-        .sorted()
-        .collect()
+fn generator(input: &str) -> Result<Vec<usize>, ParseError> {
+    let input = &parse::normalize(input);
+    let adapters = parse::lines(input, |line| parse::field(DAY, line, line))?;
+    Ok(adapters.into_iter().sorted().collect())
+}
+
+fn read_generator<R: BufRead>(reader: R) -> Result<Vec<usize>, ParseError> {
+    let adapters = parse::read_lines(DAY, reader, |line| parse::field(DAY, line, line))?;
+    Ok(adapters.into_iter().sorted().collect())
 }
 
 #[aoc(day10, part1)]
@@ -23,6 +33,7 @@ fn part1(input: &[usize]) -> usize {
             Some(diff)
         })
         .fold((0, 1), |(mut ones, mut threes), difference| {
+            event!(DAY, "difference", difference = difference);
             match difference {
                 1 => ones += 1,
                 3 => threes += 1,
@@ -55,7 +66,56 @@ fn part2(input: &[usize]) -> u64 {
             })
             .sum::<u64>();
         cache.entry(i).and_modify(|v| *v += total);
+        event!(DAY, "paths", adapter = i, paths = cache[&i]);
     }
 
     cache[goal]
 }
+
+pub struct Day10;
+
+impl Puzzle for Day10 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = DAY;
+
+    type Input = Vec<usize>;
+    type Part1 = usize;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        generator(input)
+    }
+
+    fn parse_reader<R: BufRead>(reader: R) -> Result<Self::Input, ParseError> {
+        read_generator(reader)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL_EXAMPLE: &str = "16\n10\n15\n5\n1\n11\n7\n19\n6\n12\n4";
+    const LARGE_EXAMPLE: &str = "\
+28\n33\n18\n42\n31\n14\n46\n20\n48\n47\n24\n23\n49\n45\n19\n38\n39\n11\n1\n32\n25\n35\n8\n17\n7\n9\n4\n2\n34\n10\n3";
+
+    #[test]
+    fn part1_examples() {
+        assert_eq!(part1(&generator(SMALL_EXAMPLE).unwrap()), 35);
+        assert_eq!(part1(&generator(LARGE_EXAMPLE).unwrap()), 220);
+    }
+
+    #[test]
+    fn part2_examples() {
+        assert_eq!(part2(&generator(SMALL_EXAMPLE).unwrap()), 8);
+        assert_eq!(part2(&generator(LARGE_EXAMPLE).unwrap()), 19208);
+    }
+}
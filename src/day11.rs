@@ -1,94 +1,91 @@
-use std::{collections::HashMap, iter};
+use std::{fmt, iter};
 
-type Point = (isize, isize);
+use crate::{
+    geometry::Point2,
+    grid::Grid,
+    parse::{self, ParseError},
+    trace::event,
+    Puzzle,
+};
 
-const NEIGHBORS: [Point; 8] = [
-    (-1, -1),
-    (-1, 0),
-    (-1, 1),
-    (0, -1),
-    (0, 1),
-    (1, -1),
-    (1, 0),
-    (1, 1),
-];
+const DAY: u8 = 11;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-enum Seat {
+pub enum Position {
+    Floor,
     Empty,
     Occupied,
 }
 
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Position::Floor => write!(f, "."),
+            Position::Empty => write!(f, "L"),
+            Position::Occupied => write!(f, "#"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
-struct SeatingArea {
-    seats: HashMap<Point, Seat>,
-    width: isize,
-    height: isize,
+pub struct SeatingArea {
+    seats: Grid<Position>,
 }
 
 impl SeatingArea {
-    fn count_occupied_immediate_neighbors(&self, (x, y): Point) -> usize {
-        NEIGHBORS
+    fn occupied(&self) -> usize {
+        self.seats
             .iter()
-            .map(|&(dx, dy)| (x + dx, y + dy))
-            .filter(|n| {
-                self.seats
-                    .get(&n)
-                    .map(|&seat| seat == Seat::Occupied)
-                    .unwrap_or_default()
-            })
+            .filter(|&(_, &seat)| seat == Position::Occupied)
             .count()
     }
 
-    fn count_occupied_line_of_sight_neighbors(&self, (x, y): Point) -> usize {
-        NEIGHBORS
-            .iter()
-            .filter_map(|&(dx, dy)| {
-                let neighbor = (x + dx, y + dy);
-                iter::successors(Some(neighbor), |(nx, ny)| Some((nx + dx, ny + dy)))
-                    .take_while(|&(nx, ny)| {
-                        nx >= 0 && nx <= self.width && ny >= 0 && ny <= self.height
-                    })
-                    .find_map(|n| self.seats.get(&n))
-            })
-            .filter(|&seat| *seat == Seat::Occupied)
+    fn count_occupied_immediate_neighbors(&self, point: Point2) -> usize {
+        self.seats
+            .neighbors8(point)
+            .filter(|&(_, &seat)| seat == Position::Occupied)
             .count()
     }
 
-    fn step_immedate(&self) -> Self {
-        let seats = self
-            .seats
-            .iter()
-            .map(|(&point, &seat)| {
-                let count = self.count_occupied_immediate_neighbors(point);
-                let next = match (seat, count) {
-                    (Seat::Empty, 0) => Seat::Occupied,
-                    (Seat::Occupied, count) if count >= 4 => Seat::Empty,
-                    (s, _) => s,
-                };
-                (point, next)
+    fn count_occupied_line_of_sight_neighbors(&self, point: Point2) -> usize {
+        Point2::neighborhood()
+            .filter_map(|direction| {
+                self.seats
+                    .ray(point, direction)
+                    .map(|(_, &seat)| seat)
+                    .find(|&seat| seat != Position::Floor)
             })
-            .collect();
+            .filter(|&seat| seat == Position::Occupied)
+            .count()
+    }
 
-        SeatingArea { seats, ..*self }
+    fn step<F>(&self, tolerance: usize, count_occupied: F) -> Self
+    where
+        F: Fn(&Self, Point2) -> usize + Sync,
+    {
+        let seats = Grid::par_from_fn(self.seats.width(), self.seats.height(), |point| {
+            match (self.seats[point], count_occupied(self, point)) {
+                (Position::Empty, 0) => Position::Occupied,
+                (Position::Occupied, count) if count >= tolerance => Position::Empty,
+                (seat, _) => seat,
+            }
+        });
+
+        SeatingArea { seats }
+    }
+
+    fn step_immedate(&self) -> Self {
+        self.step(4, Self::count_occupied_immediate_neighbors)
     }
 
     fn step_line_of_sight(&self) -> Self {
-        let seats = self
-            .seats
-            .iter()
-            .map(|(&point, &seat)| {
-                let count = self.count_occupied_line_of_sight_neighbors(point);
-                let next = match (seat, count) {
-                    (Seat::Empty, 0) => Seat::Occupied,
-                    (Seat::Occupied, count) if count >= 5 => Seat::Empty,
-                    (s, _) => s,
-                };
-                (point, next)
-            })
-            .collect();
+        self.step(5, Self::count_occupied_line_of_sight_neighbors)
+    }
+}
 
-        SeatingArea { seats, ..*self }
+impl fmt::Display for SeatingArea {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.seats.fmt(f)
     }
 }
 
@@ -97,47 +94,96 @@ where
     F: Fn(&SeatingArea) -> Option<SeatingArea>,
 {
     let stable = iter::successors(Some(area.clone()), step)
+        .enumerate()
+        .inspect(|(generation, area)| {
+            event!(
+                DAY,
+                "generation",
+                generation = *generation,
+                occupied = area.occupied()
+            );
+        })
+        .map(|(_, area)| area)
         .try_fold(None, |prev: Option<SeatingArea>, next| match prev {
             Some(p) if p == next => Err(p),
             _ => Ok(Some(next)),
         })
         .unwrap_err();
 
-    stable
-        .seats
-        .values()
-        .filter(|&seat| *seat == Seat::Occupied)
-        .count()
+    stable.occupied()
 }
 
 #[aoc_generator(day11)]
-fn generator(input: &str) -> SeatingArea {
-    let seats = input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            line.chars()
-                .enumerate()
-                .filter(|&(_, ch)| ch != '.')
-                .map(move |(x, _)| ((x as isize, y as isize), Seat::Empty))
-        })
-        .collect::<HashMap<Point, Seat>>();
-
-    let width = seats.keys().map(|&(x, _)| x).max().unwrap();
-    let height = seats.keys().map(|&(_, y)| y).max().unwrap();
-    SeatingArea {
-        seats,
-        width,
-        height,
-    }
+fn generator(input: &str) -> Result<SeatingArea, ParseError> {
+    let input = &parse::normalize(input);
+    let seats = Grid::parse(DAY, input, |ch| match ch {
+        'L' => Ok(Position::Empty),
+        '#' => Ok(Position::Occupied),
+        '.' => Ok(Position::Floor),
+        _ => Err("expected `L`, `#` or `.`"),
+    })?;
+
+    Ok(SeatingArea { seats })
 }
 
 #[aoc(day11, part1)]
 fn part1(area: &SeatingArea) -> usize {
-    solve(&area, |prev| Some(prev.step_immedate()))
+    solve(area, |prev| Some(prev.step_immedate()))
 }
 
 #[aoc(day11, part2)]
 fn part2(area: &SeatingArea) -> usize {
-    solve(&area, |prev| Some(prev.step_line_of_sight()))
+    solve(area, |prev| Some(prev.step_line_of_sight()))
+}
+
+pub struct Day11;
+
+impl Puzzle for Day11 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = DAY;
+
+    type Input = SeatingArea;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+L.LL.LL.LL
+LLLLLLL.LL
+L.L.L..L..
+LLLL.LL.LL
+L.LL.LL.LL
+L.LLLLL.LL
+..L.L.....
+LLLLLLLLLL
+L.LLLLLL.L
+L.LLLLL.LL";
+
+    #[test]
+    fn part1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 37);
+    }
+
+    #[test]
+    fn part2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part2(&input), 26);
+    }
 }
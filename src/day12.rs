@@ -1,6 +1,16 @@
-use std::{num::ParseIntError, ops::Add, str::FromStr};
+use std::{io::BufRead, ops::Add, str::FromStr};
+
+use crate::{
+    geometry::Point2,
+    parse::{self, ParseError},
+    trace::event,
+    Puzzle,
+};
+
+const DAY: u8 = 12;
 
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Action {
     North,
     South,
@@ -12,7 +22,7 @@ enum Action {
 }
 
 impl FromStr for Action {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let instruction = match s {
@@ -23,63 +33,56 @@ impl FromStr for Action {
             "L" => Action::Left,
             "R" => Action::Right,
             "F" => Action::Forward,
-            _ => unreachable!(),
+            _ => return Err(ParseError::new(DAY, s, "expected one of `NSEWLRF`")),
         };
 
         Ok(instruction)
     }
 }
 
-#[derive(Copy, Clone)]
-struct Instruction(Action, i32);
-
-impl FromStr for Instruction {
-    type Err = ParseIntError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (a, b) = s.split_at(1);
-        let action = a.parse().unwrap();
-        let argument = b.parse()?;
-        let operation = Instruction(action, argument);
-        Ok(operation)
+impl Action {
+    /// The unit vector for a compass heading, with north pointing up.
+    fn heading(self) -> Option<Point2> {
+        match self {
+            Action::North => Some(Point2::new(0, 1)),
+            Action::South => Some(Point2::new(0, -1)),
+            Action::East => Some(Point2::new(1, 0)),
+            Action::West => Some(Point2::new(-1, 0)),
+            _ => None,
+        }
     }
 }
 
 #[derive(Copy, Clone)]
-struct Point(i32, i32);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Instruction(Action, i64);
 
-impl Add<Instruction> for Point {
-    type Output = Point;
+impl FromStr for Instruction {
+    type Err = ParseError;
 
-    fn add(self, rhs: Instruction) -> Self::Output {
-        let Point(x, y) = self;
-        match rhs {
-            Instruction(Action::North, arg) => Point(x, y - arg),
-            Instruction(Action::South, arg) => Point(x, y + arg),
-            Instruction(Action::East, arg) => Point(x + arg, y),
-            Instruction(Action::West, arg) => Point(x - arg, y),
-            _ => unimplemented!(),
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s.chars().next().map_or(0, char::len_utf8);
+        let (a, b) = s.split_at(split);
+        let action = a.parse()?;
+        let argument = parse::field(DAY, s, b)?;
+        if matches!(action, Action::Left | Action::Right) && argument % 90 != 0 {
+            return Err(ParseError::at(
+                DAY,
+                s,
+                b,
+                "expected a multiple of 90 degrees",
+            ));
         }
-    }
-}
 
-impl Point {
-    fn rotate(&self, degrees: i32) -> Point {
-        let Point(x, y) = *self;
-        match degrees {
-            0 => Point(x, y),
-            90 | -270 => Point(-y, x),
-            180 | -180 => Point(-x, -y),
-            270 | -90 => Point(y, -x),
-            _ => unreachable!(),
-        }
+        let operation = Instruction(action, argument);
+        Ok(operation)
     }
 }
 
 #[derive(Copy, Clone)]
 struct Ship {
-    position: Point,
-    direction: Point,
+    position: Point2,
+    direction: Point2,
 }
 
 impl Add<Instruction> for Ship {
@@ -88,77 +91,131 @@ impl Add<Instruction> for Ship {
     fn add(self, rhs: Instruction) -> Self::Output {
         match rhs {
             Instruction(Action::Left, arg) => Ship {
-                direction: self.direction.rotate(-arg),
+                direction: self.direction.rotate(arg / 90),
                 ..self
             },
             Instruction(Action::Right, arg) => Ship {
-                direction: self.direction.rotate(arg),
+                direction: self.direction.rotate(-arg / 90),
+                ..self
+            },
+            Instruction(Action::Forward, arg) => Ship {
+                position: self.position + self.direction * arg,
                 ..self
             },
-            Instruction(Action::Forward, arg) => {
-                let Point(px, py) = self.position;
-                let Point(dx, dy) = self.direction;
-                Ship {
-                    position: Point(px + dx * arg, py + dy * arg),
-                    ..self
-                }
-            }
             _ => unimplemented!(),
         }
     }
 }
 
 #[aoc_generator(day12)]
-fn generator(input: &str) -> Vec<Instruction> {
-    input
-        .lines()
-        .map(str::parse)
-        .collect::<Result<_, _>>()
-        .unwrap()
+fn generator(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    let input = &parse::normalize(input);
+    parse::lines(input, str::parse)
+}
+
+fn read_generator<R: BufRead>(reader: R) -> Result<Vec<Instruction>, ParseError> {
+    parse::read_lines(DAY, reader, str::parse)
+}
+
+fn trace_ship(ship: &Ship) {
+    event!(
+        DAY,
+        "move",
+        x = ship.position.x(),
+        y = ship.position.y(),
+        dx = ship.direction.x(),
+        dy = ship.direction.y(),
+    );
 }
 
 #[aoc(day12, part1)]
-fn part1(input: &[Instruction]) -> i32 {
+fn part1(input: &[Instruction]) -> i64 {
     let state = Ship {
-        position: Point(0, 0),
-        direction: Point(1, 0),
+        position: Point2::ORIGIN,
+        direction: Point2::new(1, 0),
     };
 
-    let ship = input
-        .iter()
-        .fold(state, |ship, &instruction| match instruction {
-            Instruction(Action::Left, _)
-            | Instruction(Action::Right, _)
-            | Instruction(Action::Forward, _) => ship + instruction,
-            _ => Ship {
-                position: ship.position + instruction,
+    let ship = input.iter().fold(state, |ship, &instruction| {
+        let Instruction(action, arg) = instruction;
+        let ship = match action.heading() {
+            Some(heading) => Ship {
+                position: ship.position + heading * arg,
                 ..ship
             },
-        });
+            None => ship + instruction,
+        };
+        trace_ship(&ship);
+        ship
+    });
 
-    let Point(x, y) = ship.position;
-    x.abs() + y.abs()
+    ship.position.manhattan()
 }
 
 #[aoc(day12, part2)]
-fn part2(input: &[Instruction]) -> i32 {
+fn part2(input: &[Instruction]) -> i64 {
     let state = Ship {
-        position: Point(0, 0),
-        direction: Point(10, -1),
+        position: Point2::ORIGIN,
+        direction: Point2::new(10, 1),
     };
 
-    let ship = input
-        .iter()
-        .fold(state, |ship, &instruction| match instruction {
-            Instruction(Action::Left, _)
-            | Instruction(Action::Right, _)
-            | Instruction(Action::Forward, _) => ship + instruction,
-            _ => Ship {
-                direction: ship.direction + instruction,
+    let ship = input.iter().fold(state, |ship, &instruction| {
+        let Instruction(action, arg) = instruction;
+        let ship = match action.heading() {
+            Some(heading) => Ship {
+                direction: ship.direction + heading * arg,
                 ..ship
             },
-        });
+            None => ship + instruction,
+        };
+        trace_ship(&ship);
+        ship
+    });
+
+    ship.position.manhattan()
+}
 
-    let Point(x, y) = ship.position;
-    x.abs() + y.abs()
+pub struct Day12;
+
+impl Puzzle for Day12 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = DAY;
+
+    type Input = Vec<Instruction>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        generator(input)
+    }
+
+    fn parse_reader<R: BufRead>(reader: R) -> Result<Self::Input, ParseError> {
+        read_generator(reader)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "F10\nN3\nF7\nR90\nF11";
+
+    #[test]
+    fn part1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 25);
+    }
+
+    #[test]
+    fn part2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part2(&input), 286);
+    }
 }
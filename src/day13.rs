@@ -1,25 +1,53 @@
 use num_integer::Integer;
 
-struct State {
+use crate::{
+    parse::{self, ParseError},
+    trace::event,
+    Puzzle,
+};
+
+const DAY: u8 = 13;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct State {
     start_time: i64,
     busses: Vec<(i64, i64)>,
 }
 
 #[aoc_generator(day13)]
-fn generator(input: &str) -> State {
+fn generator(input: &str) -> Result<State, ParseError> {
+    let input = &parse::normalize(input);
     let mut lines = input.lines();
-    let start_time = lines.next().and_then(|l| l.parse().ok()).unwrap();
+    let start_time = lines
+        .next()
+        .ok_or_else(|| ParseError::new(DAY, input, "missing start time"))
+        .and_then(|l| parse::field(DAY, l, l))?;
     let busses = lines
         .next()
-        .map(|l| {
+        .ok_or_else(|| ParseError::new(DAY, input, "missing bus schedule").with_line(2))
+        .and_then(|l| {
             l.split(',')
                 .enumerate()
-                .filter_map(|(i, n)| n.parse().ok().map(|n| (i as i64, n)))
-                .collect::<Vec<(i64, i64)>>()
-        })
-        .unwrap();
+                .filter(|&(_, n)| n != "x")
+                .map(|(i, n)| {
+                    let id = parse::field(DAY, l, n)?;
+                    if id < 2 {
+                        return Err(ParseError::at(DAY, l, n, "expected a bus id of at least 2"));
+                    }
+                    Ok((i as i64, id))
+                })
+                .collect::<Result<Vec<(i64, i64)>, _>>()
+                .and_then(|busses| {
+                    if busses.is_empty() {
+                        Err(ParseError::new(DAY, l, "expected at least one bus"))
+                    } else {
+                        Ok(busses)
+                    }
+                })
+                .map_err(|e| e.with_line(2))
+        })?;
 
-    State { start_time, busses }
+    Ok(State { start_time, busses })
 }
 
 #[aoc(day13, part1)]
@@ -34,6 +62,7 @@ fn part1(state: &State) -> i64 {
                 .map(|(_, b)| (b, t))
         })
         .unwrap();
+    event!(DAY, "depart", bus = bus, time = end_time);
 
     (end_time - state.start_time) * bus
 }
@@ -48,8 +77,79 @@ fn part2(state: &State) -> i64 {
         .map(|(residue, modulus)| {
             let p = product / modulus;
             let g = p.extended_gcd(modulus);
-            residue * (g.x % modulus + modulus) * p
+            let term = residue * (g.x % modulus + modulus) * p;
+            event!(
+                DAY,
+                "residue",
+                bus = *modulus,
+                residue = residue,
+                inverse = g.x,
+                term = term,
+            );
+            term
         })
         .sum::<i64>()
         % product
 }
+
+pub struct Day13;
+
+impl Puzzle for Day13 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = DAY;
+
+    type Input = State;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "939\n7,13,x,x,59,x,31,19";
+
+    #[test]
+    fn part1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 295);
+    }
+
+    #[test]
+    fn part2_examples() {
+        let examples = [
+            ("7,13,x,x,59,x,31,19", 1068781),
+            ("17,x,13,19", 3417),
+            ("67,7,59,61", 754018),
+            ("67,x,7,59,61", 779210),
+            ("67,7,x,59,61", 1261476),
+            ("1789,37,47,1889", 1202161486),
+        ];
+        for &(busses, timestamp) in &examples {
+            let input = generator(&format!("939\n{}", busses)).unwrap();
+            assert_eq!(part2(&input), timestamp, "{}", busses);
+        }
+    }
+
+    #[test]
+    fn invalid_busses() {
+        let error = generator("939\n7,0").map(|_| ()).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 3));
+        let error = generator("939\n-7").map(|_| ()).unwrap_err();
+        assert_eq!(error.message, "expected a bus id of at least 2");
+        let error = generator("939\nx,x").map(|_| ()).unwrap_err();
+        assert_eq!(error.message, "expected at least one bus");
+    }
+}
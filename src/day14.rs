@@ -1,73 +1,187 @@
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
 use std::{
     collections::HashMap,
+    fmt,
+    io::BufRead,
     str::{self, FromStr},
 };
 
-#[derive(Debug)]
-enum Instruction {
+use crate::{
+    parse::{self, ParseError},
+    trace::event,
+    Puzzle,
+};
+
+const DAY: u8 = 14;
+const MASK_LENGTH: usize = 36;
+
+/// One line of the program. With the `serde` feature these go through the
+/// same text as the puzzle input, so deserialized instructions are checked
+/// just like parsed ones.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+pub enum Instruction {
     Mask(String),
     Memory(u64, u64),
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 struct State {
     mask: String,
     memory: HashMap<u64, u64>,
 }
 
+impl State {
+    /// Starts with every bit of the mask set to `unchanged`, the character
+    /// that leaves a bit alone in the part being solved, for programs that
+    /// write to memory before setting a mask.
+    fn new(unchanged: char) -> Self {
+        State {
+            mask: unchanged.to_string().repeat(MASK_LENGTH),
+            memory: HashMap::new(),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Instruction::Mask(mask) => write!(f, "mask = {}", mask),
+            Instruction::Memory(address, value) => write!(f, "mem[{}] = {}", address, value),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<String> for Instruction {
+    type Error = ParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Instruction> for String {
+    fn from(instruction: Instruction) -> Self {
+        instruction.to_string()
+    }
+}
+
 impl FromStr for Instruction {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(value: &str) -> Result<Self, Self::Err> {
         let parts = value.split('=').map(str::trim).collect::<Vec<&str>>();
-        let instruction = if parts[0].starts_with("mem[") {
-            let end = parts[0].bytes().position(|b| b == b']').unwrap();
-            let address = parts[0][4..end].parse().unwrap();
-            let value = parts[1].parse().unwrap();
+        let (target, argument) = match parts[..] {
+            [target, argument] => (target, argument),
+            _ => return Err(ParseError::new(DAY, value, "expected `<target> = <value>`")),
+        };
+
+        let instruction = if let Some(rest) = target.strip_prefix("mem[") {
+            let address = rest
+                .strip_suffix(']')
+                .ok_or_else(|| ParseError::at(DAY, value, target, "expected `mem[<address>]`"))
+                .and_then(|address| word(value, address, "address"))?;
+            let value = word(value, argument, "value")?;
             Instruction::Memory(address, value)
+        } else if target == "mask" {
+            if argument.chars().count() != MASK_LENGTH {
+                return Err(ParseError::at(
+                    DAY,
+                    value,
+                    argument,
+                    format!("expected a {} bit mask", MASK_LENGTH),
+                ));
+            }
+            if let Some(i) = argument.find(|ch| !matches!(ch, 'X' | '0' | '1')) {
+                return Err(parse::unexpected(DAY, argument, i, "expected one of `X01`")
+                    .relative_to(value, argument));
+            }
+            Instruction::Mask(argument.to_string())
         } else {
-            let value = parts[1].to_string();
-            Instruction::Mask(value)
+            return Err(ParseError::at(
+                DAY,
+                value,
+                target,
+                "expected `mask` or `mem`",
+            ));
         };
 
         Ok(instruction)
     }
 }
 
+/// Parses `span`, a slice of `source`, as a number that fits in the mask.
+fn word(source: &str, span: &str, name: &str) -> Result<u64, ParseError> {
+    let n = parse::field(DAY, source, span)?;
+    if n >> MASK_LENGTH != 0 {
+        return Err(ParseError::at(
+            DAY,
+            source,
+            span,
+            format!("expected a {} bit {}", MASK_LENGTH, name),
+        ));
+    }
+
+    Ok(n)
+}
+
+/// Parses one line at a time, rejecting writes to memory before the first
+/// mask has been set.
+fn program() -> impl FnMut(&str) -> Result<Instruction, ParseError> {
+    let mut masked = false;
+    move |line| match line.parse()? {
+        Instruction::Memory(..) if !masked => Err(ParseError::new(
+            DAY,
+            line,
+            "expected a `mask` before the first `mem`",
+        )),
+        instruction => {
+            masked |= matches!(instruction, Instruction::Mask(_));
+            Ok(instruction)
+        }
+    }
+}
+
 #[aoc_generator(day14)]
-fn generator(input: &str) -> Vec<Instruction> {
-    input
-        .lines()
-        .map(str::parse)
-        .collect::<Result<_, _>>()
-        .unwrap()
+fn generator(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    let input = &parse::normalize(input);
+    parse::lines(input, program())
+}
+
+fn read_generator<R: BufRead>(reader: R) -> Result<Vec<Instruction>, ParseError> {
+    parse::read_lines(DAY, reader, program())
 }
 
 #[aoc(day14, part1)]
 fn part1(instructions: &[Instruction]) -> u64 {
-    let state =
-        instructions.iter().fold(
-            State::default(),
-            |mut state, instruction| match instruction {
-                Instruction::Mask(m) => {
-                    state.mask = m.clone();
-                    state
-                }
-                Instruction::Memory(address, value) => {
-                    let value_bits = format!("{:036b}", value);
-                    let result = state
-                        .mask
-                        .chars()
-                        .zip(value_bits.chars())
-                        .map(|(m, v)| if m == 'X' { v } else { m })
-                        .collect::<String>();
-
-                    let masked_value = u64::from_str_radix(&result, 2).unwrap();
-                    *state.memory.entry(*address).or_default() = masked_value;
-                    state
-                }
-            },
-        );
+    let state = instructions.iter().fold(
+        State::new('X'),
+        |mut state, instruction| match instruction {
+            Instruction::Mask(m) => {
+                event!(DAY, "mask", mask = m.as_str());
+                state.mask = m.clone();
+                state
+            }
+            Instruction::Memory(address, value) => {
+                let value_bits = format!("{:036b}", value);
+                let result = state
+                    .mask
+                    .chars()
+                    .zip(value_bits.chars())
+                    .map(|(m, v)| if m == 'X' { v } else { m })
+                    .collect::<String>();
+
+                let masked_value = u64::from_str_radix(&result, 2).unwrap();
+                event!(DAY, "write", address = *address, value = masked_value);
+                *state.memory.entry(*address).or_default() = masked_value;
+                state
+            }
+        },
+    );
 
     state.memory.values().sum()
 }
@@ -75,52 +189,126 @@ fn part1(instructions: &[Instruction]) -> u64 {
 #[aoc(day14, part2)]
 #[allow(clippy::needless_collect)]
 fn part2(instructions: &[Instruction]) -> u64 {
-    let state =
-        instructions.iter().fold(
-            State::default(),
-            |mut state, instruction| match instruction {
-                Instruction::Mask(m) => {
-                    state.mask = m.clone();
-                    state
-                }
-                Instruction::Memory(address, value) => {
-                    let address_bits = format!("{:036b}", address);
-                    let template = state
-                        .mask
-                        .chars()
-                        .zip(address_bits.chars())
-                        .map(|(m, v)| if m == '0' { v } else { m })
-                        .collect::<String>();
-                    let floating_positions = template
-                        .chars()
-                        .enumerate()
-                        .filter(|&(_, ch)| ch == 'X')
-                        .map(|(i, _)| i)
-                        .collect::<Vec<usize>>();
-                    let max = 2u32.pow(floating_positions.len() as u32);
-                    (0..max)
-                        .filter_map(|n| {
-                            let bits = format!("{:032b}", n)
-                                .chars()
-                                .rev()
-                                .take(floating_positions.len())
-                                .enumerate()
-                                .map(|(i, c)| (floating_positions[i], c))
-                                .collect::<HashMap<usize, char>>();
-                            let address = template
-                                .chars()
-                                .enumerate()
-                                .map(|(p, ch)| if ch == 'X' { bits[&p] } else { ch })
-                                .collect::<String>();
-                            u64::from_str_radix(&address, 2).ok()
-                        })
-                        .for_each(|address| {
-                            *state.memory.entry(address).or_default() = *value;
-                        });
-                    state
-                }
-            },
-        );
+    let state = instructions.iter().fold(
+        State::new('0'),
+        |mut state, instruction| match instruction {
+            Instruction::Mask(m) => {
+                event!(DAY, "mask", mask = m.as_str());
+                state.mask = m.clone();
+                state
+            }
+            Instruction::Memory(address, value) => {
+                let address_bits = format!("{:036b}", address);
+                let template = state
+                    .mask
+                    .chars()
+                    .zip(address_bits.chars())
+                    .map(|(m, v)| if m == '0' { v } else { m })
+                    .collect::<String>();
+                let floating_positions = template
+                    .chars()
+                    .enumerate()
+                    .filter(|&(_, ch)| ch == 'X')
+                    .map(|(i, _)| i)
+                    .collect::<Vec<usize>>();
+                let max = 1u64 << floating_positions.len();
+                (0..max)
+                    .filter_map(|n| {
+                        let bits = format!("{:036b}", n)
+                            .chars()
+                            .rev()
+                            .take(floating_positions.len())
+                            .enumerate()
+                            .map(|(i, c)| (floating_positions[i], c))
+                            .collect::<HashMap<usize, char>>();
+                        let address = template
+                            .chars()
+                            .enumerate()
+                            .map(|(p, ch)| if ch == 'X' { bits[&p] } else { ch })
+                            .collect::<String>();
+                        u64::from_str_radix(&address, 2).ok()
+                    })
+                    .for_each(|address| {
+                        event!(DAY, "write", address = address, value = *value);
+                        *state.memory.entry(address).or_default() = *value;
+                    });
+                state
+            }
+        },
+    );
 
     state.memory.values().sum()
 }
+
+pub struct Day14;
+
+impl Puzzle for Day14 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = DAY;
+
+    type Input = Vec<Instruction>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        generator(input)
+    }
+
+    fn parse_reader<R: BufRead>(reader: R) -> Result<Self::Input, ParseError> {
+        read_generator(reader)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part1_example() {
+        let input = generator(
+            "\
+mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
+mem[8] = 11
+mem[7] = 101
+mem[8] = 0",
+        )
+        .unwrap();
+        assert_eq!(part1(&input), 165);
+    }
+
+    #[test]
+    fn part2_example() {
+        let input = generator(
+            "\
+mask = 000000000000000000000000000000X1001X
+mem[42] = 100
+mask = 00000000000000000000000000000000X0XX
+mem[26] = 1",
+        )
+        .unwrap();
+        assert_eq!(part2(&input), 208);
+    }
+
+    #[test]
+    fn invalid_programs() {
+        let error = generator("mem[8] = 1").unwrap_err();
+        assert_eq!(error.line, 1);
+        assert_eq!(error.message, "expected a `mask` before the first `mem`");
+        let error = generator(
+            "\
+mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
+mem[8] = 68719476736",
+        )
+        .unwrap_err();
+        assert_eq!((error.line, error.column), (2, 10));
+        assert_eq!(error.message, "expected a 36 bit value");
+    }
+}
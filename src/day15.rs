@@ -1,11 +1,19 @@
 use std::{collections::HashMap, iter};
 
+use crate::{
+    parse::{self, ParseError},
+    trace::event,
+    Puzzle,
+};
+
+const DAY: u8 = 15;
+
 fn solve(target_round: usize, seen: &HashMap<usize, usize>) -> usize {
     let mut seen = seen.clone();
     let target = target_round - seen.len();
 
     let mut round = seen.len();
-    iter::successors(Some(0), |number| {
+    let last = iter::successors(Some(0), |number| {
         let next = match seen.get(number) {
             Some(last_seen) => round - last_seen,
             None => 0,
@@ -18,26 +26,74 @@ fn solve(target_round: usize, seen: &HashMap<usize, usize>) -> usize {
     })
     .take(target)
     .last()
-    .unwrap()
+    .unwrap();
+    event!(DAY, "spoken", round = target_round, number = last);
+    last
 }
 
 #[aoc_generator(day15)]
-fn generator(input: &str) -> HashMap<usize, usize> {
+fn generator(input: &str) -> Result<HashMap<usize, usize>, ParseError> {
+    let input = &parse::normalize(input);
     input
         .split(',')
-        .map(str::parse)
-        .map(Result::unwrap)
+        .map(str::trim)
+        .map(|n| parse::field(DAY, input, n))
         .enumerate()
-        .map(|(i, s)| (s, i))
-        .collect::<HashMap<usize, usize>>()
+        .map(|(i, s)| s.map(|s| (s, i)))
+        .collect::<Result<HashMap<usize, usize>, _>>()
 }
 
 #[aoc(day15, part1)]
 fn part1(seen: &HashMap<usize, usize>) -> usize {
-    solve(2020, &seen)
+    solve(2020, seen)
 }
 
 #[aoc(day15, part2)]
 fn part2(seen: &HashMap<usize, usize>) -> usize {
-    solve(30000000, &seen)
+    solve(30000000, seen)
+}
+
+pub struct Day15;
+
+impl Puzzle for Day15 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = DAY;
+
+    type Input = HashMap<usize, usize>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part1_examples() {
+        let examples = [
+            ("0,3,6", 436),
+            ("1,3,2", 1),
+            ("2,1,3", 10),
+            ("1,2,3", 27),
+            ("2,3,1", 78),
+            ("3,2,1", 438),
+            ("3,1,2", 1836),
+        ];
+        for &(numbers, spoken) in &examples {
+            let input = generator(numbers).unwrap();
+            assert_eq!(part1(&input), spoken, "{}", numbers);
+        }
+    }
 }
@@ -1,22 +1,29 @@
-use std::{
-    collections::HashSet, convert::TryInto, num::ParseIntError, ops::RangeInclusive, str::FromStr,
+use std::{collections::HashSet, convert::TryInto, ops::RangeInclusive, str::FromStr};
+
+use crate::{
+    parse::{self, ParseError},
+    trace::event,
+    Puzzle,
 };
 
+const DAY: u8 = 16;
+
 type TicketField = u64;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Ticket {
     fields: Vec<TicketField>,
 }
 
 impl FromStr for Ticket {
-    type Err = ParseIntError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let fields = s
             .split(',')
             .map(str::trim)
-            .map(str::parse)
+            .map(|field| parse::field(DAY, s, field))
             .collect::<Result<Vec<TicketField>, _>>()?;
         let ticket = Ticket { fields };
         Ok(ticket)
@@ -32,34 +39,34 @@ impl Ticket {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Rule {
     name: String,
     ranges: [RangeInclusive<TicketField>; 2],
 }
 
 impl FromStr for Rule {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(value: &str) -> Result<Self, Self::Err> {
         let mut parts = value.split(':').map(str::trim);
-        let name = parts.next().map(str::to_string).unwrap();
+        let name = parts.next().map(str::to_string).unwrap_or_default();
         let ranges = parts
             .next()
-            .and_then(|v| {
-                v.split(" or ")
-                    .map(|r| {
-                        r.split('-')
-                            .map(str::parse)
-                            .collect::<Result<Vec<TicketField>, _>>()
-                            .map(|r| (r[0]..=r[1]))
-                            .ok()
-                    })
-                    .collect::<Option<Vec<RangeInclusive<TicketField>>>>()
+            .ok_or_else(|| ParseError::new(DAY, value, "expected `<name>: <range> or <range>`"))?
+            .split(" or ")
+            .map(|r| match r.split('-').collect::<Vec<_>>()[..] {
+                [start, end] => {
+                    Ok(parse::field(DAY, value, start)?..=parse::field(DAY, value, end)?)
+                }
+                _ => Err(ParseError::at(DAY, value, r, "expected `<start>-<end>`")),
             })
-            .unwrap();
+            .collect::<Result<Vec<RangeInclusive<TicketField>>, _>>()?;
         let rule = Rule {
             name,
-            ranges: ranges.try_into().unwrap(),
+            ranges: ranges
+                .try_into()
+                .map_err(|_| ParseError::new(DAY, value, "expected exactly two ranges"))?,
         };
         Ok(rule)
     }
@@ -71,7 +78,8 @@ impl Rule {
     }
 }
 
-struct State {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct State {
     rules: Vec<Rule>,
     valid_tickets: Vec<Ticket>,
     invalid_tickets: Vec<Ticket>,
@@ -79,36 +87,46 @@ struct State {
 }
 
 #[aoc_generator(day16)]
-fn generator(input: &str) -> State {
-    let mut parts = input.split("\n\n");
+fn generator(input: &str) -> Result<State, ParseError> {
+    let input = &parse::normalize(input);
+    let mut parts = parse::paragraphs(input);
     let rules = parts
         .next()
-        .and_then(|part| {
-            part.lines()
-                .map(str::parse)
-                .collect::<Result<Vec<Rule>, _>>()
-                .ok()
-        })
-        .unwrap();
+        .map(|(offset, part)| parse::lines(part, str::parse).map_err(|e| e.offset_lines(offset)))
+        .ok_or_else(|| ParseError::new(DAY, input, "missing rules section"))??;
     let my_ticket = parts
         .next()
-        .and_then(|part| part.lines().nth(1).and_then(|line| line.parse().ok()))
-        .unwrap();
-    let nearby_tickets: Vec<Ticket> = parts
+        .and_then(|(offset, part)| {
+            part.lines().nth(1).map(|line| {
+                line.parse()
+                    .map_err(|e: ParseError| e.with_line(offset + 2))
+            })
+        })
+        .ok_or_else(|| ParseError::new(DAY, input, "missing `your ticket:` section"))??;
+    let nearby_tickets = parts
         .next()
-        .and_then(|part| part.lines().skip(1).map(|line| line.parse().ok()).collect())
-        .unwrap();
+        .map(|(offset, part)| {
+            part.lines()
+                .enumerate()
+                .skip(1)
+                .map(|(i, line)| {
+                    line.parse()
+                        .map_err(|e: ParseError| e.with_line(offset + i + 1))
+                })
+                .collect::<Result<Vec<Ticket>, _>>()
+        })
+        .ok_or_else(|| ParseError::new(DAY, input, "missing `nearby tickets:` section"))??;
     let (invalid_tickets, valid_tickets): (Vec<Ticket>, Vec<Ticket>) = nearby_tickets
         .iter()
         .cloned()
         .partition(|ticket| ticket.is_valid(&rules));
 
-    State {
+    Ok(State {
         rules,
         valid_tickets,
         invalid_tickets,
         my_ticket,
-    }
+    })
 }
 
 #[aoc(day16, part1)]
@@ -122,11 +140,12 @@ fn part1(state: &State) -> TicketField {
                 .iter()
                 .filter(|field| state.rules.iter().all(|rule| !rule.is_match(field)))
         })
+        .inspect(|&&field| event!(DAY, "invalid", field = field))
         .sum()
 }
 
-#[aoc(day16, part2)]
-fn part2(state: &State) -> TicketField {
+/// Works out which rule applies to each field, by rule index.
+fn assign_rules(state: &State) -> Vec<usize> {
     let fields_length = state.my_ticket.fields.len();
     let mut field_matches = (0..fields_length)
         .map(|field_index| {
@@ -152,12 +171,25 @@ fn part2(state: &State) -> TicketField {
         |(mut rule_incicies, mut used_rules), (field_index, rules)| {
             let candidate_rules: HashSet<_> = rules.iter().copied().collect();
             let next = *candidate_rules.difference(&used_rules).last().unwrap();
+            event!(
+                DAY,
+                "assign",
+                field = *field_index,
+                rule = state.rules[next].name.as_str(),
+                candidates = rules.len(),
+            );
             used_rules.insert(next);
             rule_incicies[*field_index] = next;
             (rule_incicies, used_rules)
         },
     );
 
+    rule_indicies
+}
+
+#[aoc(day16, part2)]
+fn part2(state: &State) -> TicketField {
+    let rule_indicies = assign_rules(state);
     state
         .rules
         .iter()
@@ -169,3 +201,86 @@ fn part2(state: &State) -> TicketField {
         })
         .product()
 }
+
+pub struct Day16;
+
+impl Puzzle for Day16 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = DAY;
+
+    type Input = State;
+    type Part1 = TicketField;
+    type Part2 = TicketField;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part1_example() {
+        let input = generator(
+            "\
+class: 1-3 or 5-7
+row: 6-11 or 33-44
+seat: 13-40 or 45-50
+
+your ticket:
+7,1,14
+
+nearby tickets:
+7,3,47
+40,4,50
+55,2,20
+38,6,12",
+        )
+        .unwrap();
+        assert_eq!(part1(&input), 71);
+    }
+
+    #[test]
+    fn part2_example() {
+        let input = generator(
+            "\
+class: 0-1 or 4-19
+row: 0-5 or 8-19
+seat: 0-13 or 16-19
+
+your ticket:
+11,12,13
+
+nearby tickets:
+3,9,18
+15,1,5
+5,14,9",
+        )
+        .unwrap();
+        let names = assign_rules(&input)
+            .into_iter()
+            .map(|i| input.rules[i].name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["row", "class", "seat"]);
+    }
+
+    #[test]
+    fn windows_line_endings() {
+        let input = generator(
+            "class: 1-3 or 5-7  \r\nrow: 6-11 or 33-44\r\n\r\nyour ticket:\r\n7,1\r\n\r\n\
+             nearby tickets:\r\n7,3\r\n40,4\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(part1(&input), 4);
+    }
+}
@@ -1,102 +1,83 @@
 use std::{collections::HashSet, iter};
 
-use itertools::{iproduct, Itertools};
+use rayon::prelude::*;
+
+use crate::{
+    geometry::{Point, Point4},
+    grid::Grid,
+    parse::{self, ParseError},
+    trace::event,
+    Puzzle,
+};
+
+const DAY: u8 = 17;
 
 const STEPS: usize = 7;
 
 #[derive(Debug, Clone)]
-struct Dimension {
-    active_cells: HashSet<(i32, i32, i32, i32)>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dimension {
+    active_cells: HashSet<Point4>,
 }
 
 impl Dimension {
     fn step(&self) -> Dimension {
-        let (min_x, max_x) = self
-            .active_cells
-            .iter()
-            .map(|(x, _, _, _)| x)
-            .minmax()
-            .into_option()
-            .unwrap();
-        let (min_y, max_y) = self
-            .active_cells
-            .iter()
-            .map(|(_, y, _, _)| y)
-            .minmax()
-            .into_option()
-            .unwrap();
-        let (min_z, max_z) = self
-            .active_cells
-            .iter()
-            .map(|(_, _, z, _)| z)
-            .minmax()
-            .into_option()
-            .unwrap();
-        let (min_w, max_w) = self
-            .active_cells
-            .iter()
-            .map(|(_, _, _, w)| w)
-            .minmax()
-            .into_option()
-            .unwrap();
-
-        let neighbors = iproduct!(-1..=1, -1..=1, -1..=1, -1..=1)
-            .filter(|&point| point != (0, 0, 0, 0))
-            .collect::<Vec<_>>();
-        let active_cells = iproduct!(
-            min_x - 1..=max_x + 1,
-            min_y - 1..=max_y + 1,
-            min_z - 1..=max_z + 1,
-            min_w - 1..=max_w + 1
-        )
-        .filter_map(|point| {
-            let (x, y, z, w) = point;
-            let active = self.active_cells.contains(&point);
-            let active_neighbors = neighbors
-                .iter()
-                .map(|(dx, dy, dz, dw)| (x + dx, y + dy, z + dz, w + dw))
-                .filter(|neighbor| self.active_cells.contains(neighbor))
-                .count();
-            match (active, active_neighbors) {
-                (true, 2) => Some(point),
-                (_, 3) => Some(point),
-                _ => None,
-            }
-        })
-        .collect();
+        let (min, max) = match Point::bounds(self.active_cells.iter().copied()) {
+            Some(bounds) => bounds,
+            None => return self.clone(),
+        };
+
+        let margin = Point::splat(1);
+        let active_cells = Point::cuboid(min - margin, max + margin)
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .filter(|point| {
+                let active = self.active_cells.contains(point);
+                let active_neighbors = point
+                    .neighbors()
+                    .filter(|neighbor| self.active_cells.contains(neighbor))
+                    .count();
+                matches!((active, active_neighbors), (true, 2) | (_, 3))
+            })
+            .collect();
 
         Dimension { active_cells }
     }
 }
 
 #[aoc_generator(day17)]
-fn generator(input: &str) -> Dimension {
-    let active_cells = input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            line.chars()
-                .enumerate()
-                .filter_map(move |(x, ch)| match ch {
-                    '#' => Some((x as i32, y as i32, 0, 0)),
-                    _ => None,
-                })
-        })
+fn generator(input: &str) -> Result<Dimension, ParseError> {
+    let input = &parse::normalize(input);
+    let plane = Grid::parse(DAY, input, |ch| match ch {
+        '#' => Ok(true),
+        '.' => Ok(false),
+        _ => Err("expected `#` or `.`"),
+    })?;
+
+    let active_cells = plane
+        .iter()
+        .filter(|&(_, &active)| active)
+        .map(|(point, _)| Point([point.x(), point.y(), 0, 0]))
         .collect();
 
-    Dimension { active_cells }
+    Ok(Dimension { active_cells })
+}
+
+fn trace_cycle(dimension: &Dimension) {
+    event!(DAY, "cycle", active = dimension.active_cells.len());
 }
 
 #[aoc(day17, part1)]
 fn part1(start: &Dimension) -> usize {
     let start = start.clone();
     let dimension = iter::successors(Some(start), |prev| {
+        trace_cycle(prev);
         let active_cells = prev
             .step()
             .active_cells
             .iter()
             .copied()
-            .filter(|&(_, _, _, w)| w == 0)
+            .filter(|point| point[3] == 0)
             .collect();
         Some(Dimension { active_cells })
     })
@@ -110,10 +91,55 @@ fn part1(start: &Dimension) -> usize {
 #[aoc(day17, part2)]
 fn part2(start: &Dimension) -> usize {
     let start = start.clone();
-    let dimension = iter::successors(Some(start), |prev| Some(prev.step()))
-        .take(STEPS)
-        .last()
-        .unwrap();
+    let dimension = iter::successors(Some(start), |prev| {
+        trace_cycle(prev);
+        Some(prev.step())
+    })
+    .take(STEPS)
+    .last()
+    .unwrap();
 
     dimension.active_cells.len()
 }
+
+pub struct Day17;
+
+impl Puzzle for Day17 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = DAY;
+
+    type Input = Dimension;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = ".#.\n..#\n###";
+
+    #[test]
+    fn part1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 112);
+    }
+
+    #[test]
+    fn part2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part2(&input), 848);
+    }
+}
@@ -1,11 +1,20 @@
 use lazy_static::lazy_static;
 use pest::{
+    error::{ErrorVariant, LineColLocation},
     iterators::Pairs,
     prec_climber::{Assoc, Operator, PrecClimber},
     Parser,
 };
 use pest_derive::Parser;
 
+use crate::{
+    parse::{self, ParseError},
+    trace::event,
+    Puzzle,
+};
+
+const DAY: u8 = 18;
+
 lazy_static! {
     static ref PREC_CLIMBER: PrecClimber<Rule> = {
         PrecClimber::new(vec![
@@ -70,13 +79,39 @@ fn part2_evaluate(expression: Pairs<Rule>) -> i64 {
     )
 }
 
+#[aoc_generator(day18)]
+fn generator(input: &str) -> Result<String, ParseError> {
+    let input = &parse::normalize(input);
+    parse::lines(input, |line| {
+        InputParser::parse(Rule::Calculation, line)
+            .map(|_| ())
+            .map_err(|e| {
+                let column = match e.line_col {
+                    LineColLocation::Pos((_, column)) => column,
+                    LineColLocation::Span((_, column), _) => column,
+                };
+                let message = match e.variant {
+                    ErrorVariant::ParsingError { positives, .. } => {
+                        format!("expected one of {:?}", positives)
+                    }
+                    ErrorVariant::CustomError { message } => message,
+                };
+                ParseError::new(DAY, line, message).with_column(column)
+            })
+    })?;
+
+    Ok(input.to_string())
+}
+
 #[aoc(day18, part1)]
 fn part1(input: &str) -> i64 {
     input
         .lines()
         .map(|line| {
             let expression = InputParser::parse(Rule::Calculation, line).unwrap();
-            part1_evaluate(expression)
+            let value = part1_evaluate(expression);
+            event!(DAY, "line", expression = line, value = value);
+            value
         })
         .sum()
 }
@@ -87,7 +122,64 @@ fn part2(input: &str) -> i64 {
         .lines()
         .map(|line| {
             let expression = InputParser::parse(Rule::Calculation, line).unwrap();
-            part2_evaluate(expression)
+            let value = part2_evaluate(expression);
+            event!(DAY, "line", expression = line, value = value);
+            value
         })
         .sum()
 }
+
+pub struct Day18;
+
+impl Puzzle for Day18 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = DAY;
+
+    type Input = String;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLES: [(&str, i64, i64); 6] = [
+        ("1 + 2 * 3 + 4 * 5 + 6", 71, 231),
+        ("1 + (2 * 3) + (4 * (5 + 6))", 51, 51),
+        ("2 * 3 + (4 * 5)", 26, 46),
+        ("5 + (8 * 3 + 9 + 3 * 4 * 3)", 437, 1445),
+        ("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))", 12240, 669060),
+        (
+            "((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2",
+            13632,
+            23340,
+        ),
+    ];
+
+    #[test]
+    fn part1_examples() {
+        for &(expression, expected, _) in &EXAMPLES {
+            assert_eq!(part1(expression), expected, "{}", expression);
+        }
+    }
+
+    #[test]
+    fn part2_examples() {
+        for &(expression, _, expected) in &EXAMPLES {
+            assert_eq!(part2(expression), expected, "{}", expression);
+        }
+    }
+}
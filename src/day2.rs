@@ -1,58 +1,721 @@
+use std::{
+    fmt,
+    io::{self, Write},
+    ops::RangeInclusive,
+};
+
 use regex::Regex;
 
-type PasswordRule = (usize, usize, char);
-struct Entry {
-    rule: PasswordRule,
+use crate::{
+    parse::{self, ParseError},
+    report::csv_field,
+    trace::event,
+    Puzzle,
+};
+
+const DAY: u8 = 2;
+
+/// The numbers and letter at the start of a line, which each part reads as
+/// a different [`PasswordPolicy`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Rule {
+    low: usize,
+    high: usize,
+    letter: char,
+}
+
+impl Rule {
+    fn count_range(&self) -> CountRange {
+        CountRange::new(self.letter, self.low..=self.high)
+    }
+
+    fn positions(&self) -> ExclusivePositions {
+        ExclusivePositions::new(self.letter, self.low, self.high)
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{} {}", self.low, self.high, self.letter)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Entry {
+    rule: Rule,
     password: String,
 }
 
+/// Shows the entry as the line it was parsed from.
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.rule, self.password)
+    }
+}
+
 #[aoc_generator(day2)]
-fn generator(input: &str) -> Vec<Entry> {
-    let re = Regex::new(r"(\d+)-(\d+) (\w): (\w+)").unwrap();
-    input
-        .lines()
-        .map(|line| {
-            let captures = re.captures(&line).unwrap();
-
-            let a = captures[1].parse().unwrap();
-            let b = captures[2].parse().unwrap();
-            let letter = captures[3].chars().next().unwrap();
-            let password = captures[4].to_string();
-            let rule = (a, b, letter);
-            Entry { rule, password }
-        })
-        .collect()
+fn generator(input: &str) -> Result<Vec<Entry>, ParseError> {
+    let input = &parse::normalize(input);
+    parse::lines(input, parse_entry)
+}
+
+/// Parses `<low>-<high> <letter>: <password>`, where the letter is any one
+/// character and the password is any run of characters without whitespace.
+fn parse_entry(line: &str) -> Result<Entry, ParseError> {
+    let (range, rest) = line.split_once(' ').ok_or_else(|| {
+        ParseError::new(DAY, line, "expected `<low>-<high> <letter>: <password>`")
+    })?;
+    let (low, high) = range
+        .split_once('-')
+        .ok_or_else(|| ParseError::at(DAY, line, range, "expected `<low>-<high>`"))?;
+
+    let mut chars = rest.chars();
+    let letter = chars
+        .next()
+        .filter(|c| !c.is_whitespace())
+        .ok_or_else(|| ParseError::at(DAY, line, rest, "expected a letter"))?;
+    let rest = chars.as_str();
+    let password = rest
+        .strip_prefix(':')
+        .map(str::trim_start)
+        .ok_or_else(|| ParseError::at(DAY, line, rest, "expected `:` after the letter"))?;
+    if password.is_empty() {
+        return Err(ParseError::at(DAY, line, password, "missing password"));
+    }
+    if let Some(index) = password.find(char::is_whitespace) {
+        let index = line.len() - password.len() + index;
+        return Err(parse::unexpected(
+            DAY,
+            line,
+            index,
+            "whitespace in password",
+        ));
+    }
+
+    let rule = Rule {
+        low: parse::field(DAY, line, low)?,
+        high: parse::field(DAY, line, high)?,
+        letter,
+    };
+    let password = password.to_string();
+    Ok(Entry { rule, password })
+}
+
+/// A rule that passwords are checked against. Its [`Display`] says what the
+/// rule expects of a password.
+///
+/// [`Display`]: fmt::Display
+pub trait PasswordPolicy: fmt::Display {
+    /// Says why `password` breaks the policy, or returns `None` if it
+    /// doesn't.
+    fn explain(&self, password: &str) -> Option<String>;
+
+    fn allows(&self, password: &str) -> bool {
+        self.explain(password).is_none()
+    }
+
+    /// A policy that only allows passwords that both policies allow.
+    fn and<P: PasswordPolicy>(self, other: P) -> And<Self, P>
+    where
+        Self: Sized,
+    {
+        And(self, other)
+    }
+
+    /// A policy that allows passwords that either policy allows.
+    fn or<P: PasswordPolicy>(self, other: P) -> Or<Self, P>
+    where
+        Self: Sized,
+    {
+        Or(self, other)
+    }
+
+    /// A policy that allows exactly the passwords this one does not.
+    fn not(self) -> Not<Self>
+    where
+        Self: Sized,
+    {
+        Not(self)
+    }
+}
+
+impl<P: PasswordPolicy + ?Sized> PasswordPolicy for &P {
+    fn explain(&self, password: &str) -> Option<String> {
+        (**self).explain(password)
+    }
+
+    fn allows(&self, password: &str) -> bool {
+        (**self).allows(password)
+    }
+}
+
+impl<P: PasswordPolicy + ?Sized> PasswordPolicy for Box<P> {
+    fn explain(&self, password: &str) -> Option<String> {
+        (**self).explain(password)
+    }
+
+    fn allows(&self, password: &str) -> bool {
+        (**self).allows(password)
+    }
+}
+
+/// The letter must appear a number of times within the range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CountRange {
+    letter: char,
+    range: RangeInclusive<usize>,
+}
+
+impl CountRange {
+    pub fn new(letter: char, range: RangeInclusive<usize>) -> Self {
+        CountRange { letter, range }
+    }
+}
+
+impl fmt::Display for CountRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} to {} of `{}`",
+            self.range.start(),
+            self.range.end(),
+            self.letter
+        )
+    }
 }
 
-fn part1_policy((minimum, maximum, letter): PasswordRule, password: &str) -> bool {
-    let count = password.chars().filter(|&c| c == letter).count();
-    count >= minimum && count <= maximum
+impl PasswordPolicy for CountRange {
+    fn explain(&self, password: &str) -> Option<String> {
+        let count = password.chars().filter(|&c| c == self.letter).count();
+        if self.range.contains(&count) {
+            None
+        } else {
+            Some(format!(
+                "has {} of `{}`, expected {}",
+                count, self.letter, self
+            ))
+        }
+    }
 }
 
-fn part2_policy((first, last, letter): PasswordRule, password: &str) -> bool {
-    let chars = password.chars().collect::<Vec<char>>();
-    let a = chars[first - 1];
-    let b = chars[last - 1];
+/// The letter must be at exactly one of two positions, counted in
+/// characters from 1. Positions outside the password, including 0, hold no
+/// character, so the letter is never there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExclusivePositions {
+    letter: char,
+    first: usize,
+    second: usize,
+}
+
+impl ExclusivePositions {
+    pub fn new(letter: char, first: usize, second: usize) -> Self {
+        ExclusivePositions {
+            letter,
+            first,
+            second,
+        }
+    }
+
+    fn at(password: &str, position: usize) -> Option<char> {
+        position
+            .checked_sub(1)
+            .and_then(|index| password.chars().nth(index))
+    }
+
+    fn describe(password: &str, position: usize) -> String {
+        match Self::at(password, position) {
+            Some(c) => format!("`{}` at position {}", c, position),
+            None => format!("nothing at position {}", position),
+        }
+    }
+}
+
+impl fmt::Display for ExclusivePositions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "`{}` at exactly one of positions {} and {}",
+            self.letter, self.first, self.second
+        )
+    }
+}
 
-    (a == letter) ^ (b == letter)
+impl PasswordPolicy for ExclusivePositions {
+    fn explain(&self, password: &str) -> Option<String> {
+        let first = Self::at(password, self.first) == Some(self.letter);
+        let second = Self::at(password, self.second) == Some(self.letter);
+        if first != second {
+            None
+        } else {
+            Some(format!(
+                "has {} and {}, expected {}",
+                Self::describe(password, self.first),
+                Self::describe(password, self.second),
+                self
+            ))
+        }
+    }
 }
 
-fn solve<F>(input: &[Entry], policy: F) -> usize
+/// None of the characters may appear.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForbiddenChars(pub String);
+
+impl fmt::Display for ForbiddenChars {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "none of `{}`", self.0)
+    }
+}
+
+impl PasswordPolicy for ForbiddenChars {
+    fn explain(&self, password: &str) -> Option<String> {
+        password
+            .chars()
+            .find(|&c| self.0.contains(c))
+            .map(|c| format!("has `{}`, expected {}", c, self))
+    }
+}
+
+/// The password must be at least this many characters long.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MinLength(pub usize);
+
+impl fmt::Display for MinLength {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "at least {} characters", self.0)
+    }
+}
+
+impl PasswordPolicy for MinLength {
+    fn explain(&self, password: &str) -> Option<String> {
+        let length = password.chars().count();
+        if length >= self.0 {
+            None
+        } else {
+            Some(format!("has {} characters, expected {}", length, self))
+        }
+    }
+}
+
+/// The password must contain a match for the regex.
+#[derive(Debug, Clone)]
+pub struct Matches(pub Regex);
+
+impl fmt::Display for Matches {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a match for `{}`", self.0)
+    }
+}
+
+impl PasswordPolicy for Matches {
+    fn explain(&self, password: &str) -> Option<String> {
+        if self.0.is_match(password) {
+            None
+        } else {
+            Some(format!("has no match for `{}`", self.0))
+        }
+    }
+}
+
+/// See [`PasswordPolicy::and`].
+#[derive(Debug, Clone)]
+pub struct And<A, B>(A, B);
+
+impl<A: fmt::Display, B: fmt::Display> fmt::Display for And<A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({} and {})", self.0, self.1)
+    }
+}
+
+impl<A: PasswordPolicy, B: PasswordPolicy> PasswordPolicy for And<A, B> {
+    fn explain(&self, password: &str) -> Option<String> {
+        match (self.0.explain(password), self.1.explain(password)) {
+            (Some(a), Some(b)) => Some(format!("{}; {}", a, b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    fn allows(&self, password: &str) -> bool {
+        self.0.allows(password) && self.1.allows(password)
+    }
+}
+
+/// See [`PasswordPolicy::or`].
+#[derive(Debug, Clone)]
+pub struct Or<A, B>(A, B);
+
+impl<A: fmt::Display, B: fmt::Display> fmt::Display for Or<A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({} or {})", self.0, self.1)
+    }
+}
+
+impl<A: PasswordPolicy, B: PasswordPolicy> PasswordPolicy for Or<A, B> {
+    fn explain(&self, password: &str) -> Option<String> {
+        let a = self.0.explain(password)?;
+        let b = self.1.explain(password)?;
+        Some(format!("{}; {}", a, b))
+    }
+
+    fn allows(&self, password: &str) -> bool {
+        self.0.allows(password) || self.1.allows(password)
+    }
+}
+
+/// See [`PasswordPolicy::not`].
+#[derive(Debug, Clone)]
+pub struct Not<P>(P);
+
+impl<P: fmt::Display> fmt::Display for Not<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "not {}", self.0)
+    }
+}
+
+impl<P: PasswordPolicy> PasswordPolicy for Not<P> {
+    fn explain(&self, password: &str) -> Option<String> {
+        if self.0.allows(password) {
+            Some(format!("has {}, expected {}", self.0, self))
+        } else {
+            None
+        }
+    }
+
+    fn allows(&self, password: &str) -> bool {
+        !self.0.allows(password)
+    }
+}
+
+/// Counts the entries whose password is allowed by the policy that
+/// `policy` reads from their rule.
+fn solve<P, F>(input: &[Entry], policy: F) -> usize
 where
-    F: Fn(PasswordRule, &str) -> bool,
+    P: PasswordPolicy,
+    F: Fn(&Rule) -> P,
 {
     input
         .iter()
-        .filter(|entry| policy(entry.rule, &entry.password))
+        .enumerate()
+        .filter(|(i, entry)| {
+            let allowed = policy(&entry.rule).allows(&entry.password);
+            event!(DAY, "check", entry = *i, allowed = allowed as u8);
+            allowed
+        })
         .count()
 }
 
+/// An entry whose password breaks at least one of the puzzle's policies,
+/// with what was seen in the password.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding<'a> {
+    /// The line of the input the entry was parsed from, counted from 1.
+    pub line: usize,
+    pub entry: &'a Entry,
+    /// How many times the rule's letter appears in the password.
+    pub count: usize,
+    /// Where the rule's letter appears, counted in characters from 1.
+    pub positions: Vec<usize>,
+    /// The name of each policy broken, part 1's `count` and part 2's
+    /// `positions`, with the reason it was broken.
+    pub violations: Vec<(&'static str, String)>,
+}
+
+/// Lists every entry that breaks either part's policy, in input order. The
+/// entries must be those parsed from the input, one per line.
+pub fn audit(input: &[Entry]) -> Vec<Finding<'_>> {
+    input
+        .iter()
+        .enumerate()
+        .filter_map(|(i, entry)| {
+            let Entry { rule, password } = entry;
+            let violations = vec![
+                ("count", rule.count_range().explain(password)),
+                ("positions", rule.positions().explain(password)),
+            ]
+            .into_iter()
+            .filter_map(|(name, reason)| reason.map(|reason| (name, reason)))
+            .collect::<Vec<_>>();
+            if violations.is_empty() {
+                return None;
+            }
+
+            let positions = password
+                .chars()
+                .enumerate()
+                .filter(|&(_, c)| c == rule.letter)
+                .map(|(i, _)| i + 1)
+                .collect::<Vec<_>>();
+            Some(Finding {
+                line: i + 1,
+                entry,
+                count: positions.len(),
+                positions,
+                violations,
+            })
+        })
+        .collect()
+}
+
+/// Writes the findings of an [`audit`] as CSV with a header row. Positions
+/// are separated by spaces and violations by `; `, each as
+/// `<policy>: <reason>`.
+pub fn write_csv<W: Write>(mut out: W, findings: &[Finding<'_>]) -> io::Result<()> {
+    writeln!(out, "line,rule,password,count,positions,violations")?;
+    for finding in findings {
+        let positions = finding
+            .positions
+            .iter()
+            .map(usize::to_string)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let violations = finding
+            .violations
+            .iter()
+            .map(|(name, reason)| format!("{}: {}", name, reason))
+            .collect::<Vec<_>>()
+            .join("; ");
+        writeln!(
+            out,
+            "{},{},{},{},{},{}",
+            finding.line,
+            csv_field(&finding.entry.rule.to_string()),
+            csv_field(&finding.entry.password),
+            finding.count,
+            positions,
+            csv_field(&violations)
+        )?;
+    }
+    Ok(())
+}
+
 #[aoc(day2, part1)]
 fn part1(input: &[Entry]) -> usize {
-    solve(input, part1_policy)
+    solve(input, Rule::count_range)
 }
 
 #[aoc(day2, part2)]
 fn part2(input: &[Entry]) -> usize {
-    solve(input, part2_policy)
+    solve(input, Rule::positions)
+}
+
+pub struct Day2;
+
+impl Puzzle for Day2 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = DAY;
+
+    type Input = Vec<Entry>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc";
+
+    #[test]
+    fn part1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 2);
+    }
+
+    #[test]
+    fn part2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part2(&input), 1);
+    }
+
+    #[test]
+    fn policies_explain_failures() {
+        let count = CountRange::new('a', 1..=3);
+        assert!(count.allows("abcde"));
+        assert_eq!(
+            count.explain("aaaab").unwrap(),
+            "has 4 of `a`, expected 1 to 3 of `a`"
+        );
+
+        let positions = ExclusivePositions::new('c', 2, 9);
+        assert!(positions.allows("acdefghij"));
+        assert_eq!(
+            positions.explain("ccccccccc").unwrap(),
+            "has `c` at position 2 and `c` at position 9, \
+             expected `c` at exactly one of positions 2 and 9"
+        );
+        assert_eq!(
+            positions.explain("abdefghij").unwrap(),
+            "has `b` at position 2 and `j` at position 9, \
+             expected `c` at exactly one of positions 2 and 9"
+        );
+
+        assert_eq!(
+            ForbiddenChars("xyz".to_string()).explain("abyc").unwrap(),
+            "has `y`, expected none of `xyz`"
+        );
+        assert_eq!(
+            MinLength(8).explain("short").unwrap(),
+            "has 5 characters, expected at least 8 characters"
+        );
+        let digit = Matches(Regex::new(r"\d").unwrap());
+        assert!(digit.allows("abc1"));
+        assert_eq!(digit.explain("abc").unwrap(), "has no match for `\\d`");
+    }
+
+    #[test]
+    fn policies_combine() {
+        let policy = MinLength(4)
+            .and(ForbiddenChars("!".to_string()))
+            .or(CountRange::new('z', 2..=2).not());
+        assert_eq!(
+            policy.to_string(),
+            "((at least 4 characters and none of `!`) or not 2 to 2 of `z`)"
+        );
+        assert!(policy.allows("abcd"));
+        assert!(policy.allows("ab!"));
+        assert!(!policy.allows("zz"));
+        assert_eq!(
+            policy.explain("zz!").unwrap(),
+            "has 3 characters, expected at least 4 characters; \
+             has `!`, expected none of `!`; \
+             has 2 to 2 of `z`, expected not 2 to 2 of `z`"
+        );
+
+        let boxed: Vec<Box<dyn PasswordPolicy>> = vec![
+            Box::new(MinLength(2)),
+            Box::new(CountRange::new('a', 0..=0)),
+        ];
+        assert!(boxed.iter().all(|policy| policy.allows("bb")));
+        assert!(!boxed.iter().all(|policy| policy.allows("ab")));
+    }
+
+    #[test]
+    fn positions_outside_the_password() {
+        assert!(ExclusivePositions::new('a', 1, 9).allows("ab"));
+        assert!(ExclusivePositions::new('a', 0, 2).allows("ba"));
+        assert_eq!(
+            ExclusivePositions::new('a', 0, 5).explain("aaa").unwrap(),
+            "has nothing at position 0 and nothing at position 5, \
+             expected `a` at exactly one of positions 0 and 5"
+        );
+
+        let input = generator("0-9 a: abc\n2-3 é: çéé").unwrap();
+        assert_eq!(part2(&input), 0);
+        assert_eq!(part1(&input), 2);
+    }
+
+    #[test]
+    fn passwords_may_hold_any_characters() {
+        let input = generator("1-3 !: p@ss!\n3-4 ü: Grüße-ü\n1-1 ::a:b").unwrap();
+        assert_eq!(input[0].password, "p@ss!");
+        assert_eq!(input[1].rule.letter, 'ü');
+        assert_eq!(input[2].password, "a:b");
+        assert_eq!(part1(&input), 2);
+        assert_eq!(part2(&input), 1);
+    }
+
+    #[test]
+    fn malformed_lines() {
+        let error = |input| {
+            let e = generator(input).map(|_| ()).unwrap_err();
+            (e.line, e.column, e.text, e.message)
+        };
+
+        assert_eq!(
+            error("1-3 a: abc\n1-3"),
+            (
+                2,
+                1,
+                "1-3".to_string(),
+                "expected `<low>-<high> <letter>: <password>`".to_string()
+            )
+        );
+        assert_eq!(
+            error("13 a: abc"),
+            (
+                1,
+                1,
+                "13".to_string(),
+                "expected `<low>-<high>`".to_string()
+            )
+        );
+        assert_eq!(error("1-x a: abc").1, 3);
+        assert_eq!(
+            error("1-3 ab: abc"),
+            (
+                1,
+                6,
+                "b: abc".to_string(),
+                "expected `:` after the letter".to_string()
+            )
+        );
+        assert_eq!(
+            error("1-3 é:"),
+            (1, 7, "".to_string(), "missing password".to_string())
+        );
+        assert_eq!(
+            error("1-3 é: ab\tc"),
+            (
+                1,
+                10,
+                "\t".to_string(),
+                "whitespace in password".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn audit_lists_failing_entries() {
+        let input = generator(EXAMPLE).unwrap();
+        let findings = audit(&input);
+        assert_eq!(findings.len(), 2);
+
+        assert_eq!(findings[0].line, 2);
+        assert_eq!(findings[0].entry.to_string(), "1-3 b: cdefg");
+        assert_eq!(findings[0].count, 0);
+        assert!(findings[0].positions.is_empty());
+        assert_eq!(
+            findings[0]
+                .violations
+                .iter()
+                .map(|&(name, _)| name)
+                .collect::<Vec<_>>(),
+            ["count", "positions"]
+        );
+
+        assert_eq!(findings[1].line, 3);
+        assert_eq!(findings[1].count, 9);
+        assert_eq!(findings[1].positions, (1..=9).collect::<Vec<_>>());
+        assert_eq!(findings[1].violations.len(), 1);
+        assert_eq!(findings[1].violations[0].0, "positions");
+    }
+
+    #[test]
+    fn audit_exports_csv() {
+        let input = generator("1-3 a: abcde\n2-2 ,: a,\"b").unwrap();
+        let mut csv = Vec::new();
+        write_csv(&mut csv, &audit(&input)).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "line,rule,password,count,positions,violations\n\
+             2,\"2-2 ,\",\"a,\"\"b\",1,2,\"count: has 1 of `,`, expected 2 to 2 of `,`; \
+             positions: has `,` at position 2 and `,` at position 2, \
+             expected `,` at exactly one of positions 2 and 2\"\n"
+        );
+    }
 }
@@ -1,86 +1,520 @@
-use std::{collections::HashMap, iter, ops::Index};
+use std::{convert::TryFrom, fmt, io::BufRead, ops::RangeInclusive};
 
-type Point = (usize, usize);
-const ORIGIN: Point = (0, 0);
+use crate::{
+    geometry::Point2,
+    parse::{self, ParseError},
+    trace::event,
+    Puzzle,
+};
 
-#[derive(Copy, Clone, PartialEq)]
-enum Square {
-    Tree,
-    Open,
+const DAY: u8 = 3;
+
+const BITS: usize = u64::BITS as usize;
+/// How many values of `dy` [`TreeMap::search_slopes`] tries in each pass.
+const SEARCH_BATCH: i64 = 1024;
+
+/// The toboggan map, which repeats to the right forever. Each row is packed
+/// into words of bits, set for trees, so that a map with millions of rows
+/// takes an eighth of the space of its text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TreeMap {
+    width: usize,
+    height: usize,
+    /// Words per row.
+    stride: usize,
+    bits: Vec<u64>,
 }
 
-struct Grid {
-    pattern: HashMap<Point, Square>,
-    pattern_width: usize,
-    pattern_height: usize,
+impl TreeMap {
+    fn new() -> Self {
+        TreeMap {
+            width: 0,
+            height: 0,
+            stride: 0,
+            bits: Vec::new(),
+        }
+    }
+
+    /// Packs a line onto the bottom of the map. The first line sets the
+    /// width of every row.
+    fn push_row(&mut self, line: &str) -> Result<(), ParseError> {
+        if self.height == 0 {
+            self.width = line.chars().count();
+            self.stride = self.width.div_ceil(BITS);
+        }
+
+        let start = self.bits.len();
+        self.bits.resize(start + self.stride, 0);
+        let mut width = 0;
+        for (i, ch) in line.char_indices() {
+            match ch {
+                '#' if width < self.width => {
+                    self.bits[start + width / BITS] |= 1 << (width % BITS);
+                }
+                '#' | '.' => {}
+                _ => return Err(parse::unexpected(DAY, line, i, "expected `#` or `.`")),
+            }
+            width += 1;
+        }
+        if width != self.width {
+            let message = format!("expected a row of {} cells, found {}", self.width, width);
+            return Err(ParseError::new(DAY, line, message));
+        }
+
+        self.height += 1;
+        Ok(())
+    }
+
+    fn finish(self, input: &str) -> Result<Self, ParseError> {
+        if self.width == 0 {
+            return Err(ParseError::new(DAY, input, "empty grid"));
+        }
+        Ok(self)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether there is a tree at column `x` of row `y`, where columns past
+    /// the right edge wrap around.
+    ///
+    /// # Panics
+    ///
+    /// If `y` is not a row of the map.
+    pub fn is_tree(&self, x: usize, y: usize) -> bool {
+        let x = x % self.width;
+        self.bits[y * self.stride + x / BITS] >> (x % BITS) & 1 == 1
+    }
+
+    /// Counts the trees hit going from the top-left corner to the bottom by
+    /// steps of `dx` right and `dy` down.
+    pub fn trees_on_slope(&self, dx: usize, dy: usize) -> usize {
+        self.trees_on_slopes(&[(dx, dy)])[0]
+    }
+
+    /// Counts the trees hit on each of the slopes, as [`trees_on_slope`]
+    /// would, visiting each row once for all of them.
+    ///
+    /// # Panics
+    ///
+    /// If any slope has a `dy` of 0, as it would never reach the bottom.
+    ///
+    /// [`trees_on_slope`]: TreeMap::trees_on_slope
+    pub fn trees_on_slopes(&self, slopes: &[(usize, usize)]) -> Vec<usize> {
+        let slopes = slopes
+            .iter()
+            .map(|&(dx, dy)| {
+                let dy = i64::try_from(dy).unwrap_or(i64::MAX);
+                Slope::new((dx % self.width) as i64, dy).expect("slopes must go down the map")
+            })
+            .collect::<Vec<_>>();
+        self.trees_on_paths(Point2::ORIGIN, &slopes)
+    }
+
+    /// Counts the trees hit going from `start` to the bottom along `slope`.
+    pub fn trees_from(&self, start: Point2, slope: Slope) -> usize {
+        self.trees_on_paths(start, &[slope])[0]
+    }
+
+    /// Counts the trees hit along each slope from `start`, visiting each row
+    /// once for all of them. The start can be anywhere: columns wrap around
+    /// both ways, and only landings on rows of the map are counted, so a
+    /// start above the top joins the map part way along its path.
+    pub fn trees_on_paths(&self, start: Point2, slopes: &[Slope]) -> Vec<usize> {
+        let width = self.width as i64;
+        let height = self.height as i64;
+
+        // Each path's next landing, as a column within the map and a row,
+        // with steps reduced by the width so that columns never overflow.
+        let mut paths = slopes
+            .iter()
+            .map(|slope| {
+                let (dx, dy) = (slope.dx.rem_euclid(width), slope.dy as i128);
+                let steps = match start.y() as i128 {
+                    y if y < 0 => (-y + dy - 1) / dy,
+                    _ => 0,
+                };
+                let column = (start.x().rem_euclid(width) as i128
+                    + steps % width as i128 * dx as i128)
+                    % width as i128;
+                let row = start.y() as i128 + steps * dy;
+                (column as i64, row as i64, dx, slope.dy)
+            })
+            .collect::<Vec<_>>();
+        let mut trees = vec![0; slopes.len()];
+
+        for y in start.y().max(0)..height {
+            for (i, (column, row, dx, dy)) in paths.iter_mut().enumerate() {
+                if *row == y {
+                    trees[i] += self.is_tree(*column as usize, y as usize) as usize;
+                    *column = (*column + *dx) % width;
+                    *row = row.saturating_add(*dy);
+                }
+            }
+        }
+
+        trees
+    }
+
+    /// Tries every slope with `dx` in `dxs` and `dy` in `dys` from `start`
+    /// and returns the one that hits the fewest or most trees along with how
+    /// many. Ties go to the slope with the smallest `dy`, then the smallest
+    /// `dx`. Slopes whose `dx` differ by a multiple of the width take the
+    /// same path, so only the first of each is tried. Returns `None` if no
+    /// `dy` in `dys` is positive.
+    ///
+    /// Every `dy` that steps past the bottom row from `start` hits the same
+    /// trees as the smallest one, so `dys` is cut off there and the cost is
+    /// bounded by the rows below `start`, not the size of `dys`. The `dy`
+    /// values left are tried a batch at a time, each batch one pass over
+    /// the rows, so only a batch's worth of slopes is held at once.
+    pub fn search_slopes(
+        &self,
+        start: Point2,
+        dxs: RangeInclusive<i64>,
+        dys: RangeInclusive<i64>,
+        goal: Goal,
+    ) -> Option<(Slope, usize)> {
+        let first = (*dys.start()).max(1);
+        let last = (*dys.end()).min((self.height as i64).saturating_sub(start.y()).max(first));
+
+        let mut best = None;
+        let mut dy = first;
+        while dy <= last {
+            let batch = dy.saturating_add(SEARCH_BATCH - 1).min(last);
+            let slopes = (dy..=batch)
+                .flat_map(|dy| dxs.clone().take(self.width).map(move |dx| Slope { dx, dy }))
+                .collect::<Vec<_>>();
+            let trees = self.trees_on_paths(start, &slopes);
+
+            best = slopes
+                .into_iter()
+                .zip(trees)
+                .fold(best, |best, (slope, trees)| match best {
+                    Some((_, most)) if goal == Goal::Most && trees <= most => best,
+                    Some((_, fewest)) if goal == Goal::Fewest && trees >= fewest => best,
+                    _ => Some((slope, trees)),
+                });
+            match batch.checked_add(1) {
+                Some(next) => dy = next,
+                None => break,
+            }
+        }
+
+        best
+    }
+}
+
+/// A step of `dx` columns right, or left when negative, and `dy` rows
+/// down. The toboggan only lands at the end of each step.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Slope {
+    dx: i64,
+    dy: i64,
+}
+
+impl Slope {
+    /// Returns `None` unless the slope goes down, as otherwise it would
+    /// never reach the bottom.
+    pub fn new(dx: i64, dy: i64) -> Option<Self> {
+        if dy > 0 {
+            Some(Slope { dx, dy })
+        } else {
+            None
+        }
+    }
+
+    pub fn dx(self) -> i64 {
+        self.dx
+    }
+
+    pub fn dy(self) -> i64 {
+        self.dy
+    }
 }
 
-impl Grid {
-    fn new(pattern: HashMap<Point, Square>) -> Self {
-        let pattern_width = pattern.keys().map(|&(x, _)| x).max().unwrap();
-        let pattern_height = pattern.keys().map(|&(_, y)| y).max().unwrap();
-        Self {
-            pattern,
-            pattern_width,
-            pattern_height,
+impl fmt::Display for Slope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.dx {
+            dx if dx < 0 => write!(f, "left {}, down {}", dx.unsigned_abs(), self.dy),
+            dx => write!(f, "right {}, down {}", dx, self.dy),
         }
     }
 }
 
-impl Index<Point> for Grid {
-    type Output = Square;
+/// The slopes whose tree counts are multiplied for part 2.
+pub const PUZZLE_SLOPES: [Slope; 5] = [
+    Slope { dx: 1, dy: 1 },
+    Slope { dx: 3, dy: 1 },
+    Slope { dx: 5, dy: 1 },
+    Slope { dx: 7, dy: 1 },
+    Slope { dx: 1, dy: 2 },
+];
+
+/// Whether [`TreeMap::search_slopes`] looks for the fewest or most trees.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Goal {
+    Fewest,
+    Most,
+}
 
-    fn index(&self, (x, y): Point) -> &Self::Output {
-        let x = x % (self.pattern_width + 1);
-        &self.pattern[&(x, y)]
+impl fmt::Display for TreeMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                write!(f, "{}", if self.is_tree(x, y) { '#' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
     }
 }
 
 #[aoc_generator(day3)]
-fn generator(input: &str) -> Grid {
-    let pattern = input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            line.chars()
-                .enumerate()
-                .map(move |(x, ch)| {
-                    let square = match ch {
-                        '#' => Square::Tree,
-                        '.' => Square::Open,
-                        _ => unreachable!(),
-                    };
-                    ((x, y), square)
-                })
-                .collect::<HashMap<_, _>>()
-        })
-        .collect();
-
-    Grid::new(pattern)
+fn generator(input: &str) -> Result<TreeMap, ParseError> {
+    let input = &parse::normalize(input);
+    let mut map = TreeMap::new();
+    parse::lines(input, |line| map.push_row(line))?;
+    map.finish(input)
+}
+
+fn read_generator<R: BufRead>(reader: R) -> Result<TreeMap, ParseError> {
+    let mut map = TreeMap::new();
+    parse::read_lines(DAY, reader, |line| map.push_row(line))?;
+    map.finish("")
 }
 
-fn path(dx: usize, dy: usize) -> impl Iterator<Item = Point> {
-    iter::successors(Some(ORIGIN), move |(x, y)| Some((x + dx, y + dy)))
+fn slope_trees(map: &TreeMap, slopes: &[Slope]) -> Vec<usize> {
+    let trees = map.trees_on_paths(Point2::ORIGIN, slopes);
+    for (slope, &trees) in slopes.iter().zip(&trees) {
+        event!(DAY, "slope", dx = slope.dx, dy = slope.dy, trees = trees);
+    }
+    trees
 }
 
 #[aoc(day3, part1)]
-fn part1(grid: &Grid) -> usize {
-    path(3, 1)
-        .take_while(|&(_, y)| y <= grid.pattern_height)
-        .filter(|&p| grid[p] == Square::Tree)
-        .count()
+fn part1(map: &TreeMap) -> usize {
+    slope_trees(map, &PUZZLE_SLOPES[1..2])[0]
 }
 
 #[aoc(day3, part2)]
-fn part2(grid: &Grid) -> usize {
-    let paths = &[(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
-    paths
-        .iter()
-        .map(|&(dx, dy)| {
-            path(dx, dy)
-                .take_while(|&(_, y)| y <= grid.pattern_height)
-                .filter(|&p| grid[p] == Square::Tree)
-                .count()
-        })
-        .product()
+fn part2(map: &TreeMap) -> usize {
+    slope_trees(map, &PUZZLE_SLOPES).into_iter().product()
+}
+
+pub struct Day3;
+
+impl Puzzle for Day3 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = DAY;
+
+    type Input = TreeMap;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        generator(input)
+    }
+
+    fn parse_reader<R: BufRead>(reader: R) -> Result<Self::Input, ParseError> {
+        read_generator(reader)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#";
+
+    #[test]
+    fn part1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 7);
+    }
+
+    #[test]
+    fn part2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part2(&input), 336);
+    }
+
+    #[test]
+    fn wide_rows_span_words() {
+        let row = format!("{}#{}#", ".".repeat(63), ".".repeat(70));
+        let input = generator(&format!("{}\n{}", row, row)).unwrap();
+        assert_eq!(input.width(), 135);
+        assert!(input.is_tree(63, 1));
+        assert!(input.is_tree(134, 0));
+        assert!(input.is_tree(135 + 63, 0));
+        assert!(!input.is_tree(64, 0));
+        assert_eq!(input.to_string(), format!("{}\n{}\n", row, row));
+        assert_eq!(
+            input.trees_on_slopes(&[(63, 1), (134, 1), (71, 1)]),
+            [1, 1, 0]
+        );
+    }
+
+    #[test]
+    fn slopes_match_one_at_a_time() {
+        let input = generator(EXAMPLE).unwrap();
+        let slopes = [
+            (1, 1),
+            (3, 1),
+            (5, 1),
+            (7, 1),
+            (1, 2),
+            (0, 3),
+            (100, 1),
+            (2, 20),
+        ];
+        let each = slopes
+            .iter()
+            .map(|&(dx, dy)| input.trees_on_slope(dx, dy))
+            .collect::<Vec<_>>();
+        assert_eq!(input.trees_on_slopes(&slopes), each);
+        assert_eq!(each[..5], [2, 7, 3, 4, 2]);
+    }
+
+    #[test]
+    fn streamed_input_matches() {
+        let streamed = read_generator(format!("{}\r\n\n", EXAMPLE).as_bytes()).unwrap();
+        assert_eq!(streamed, generator(EXAMPLE).unwrap());
+    }
+
+    #[test]
+    fn malformed_rows() {
+        let error = generator("..#\n.x.").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 2));
+        let error = generator("..#\n....").unwrap_err();
+        assert_eq!(error.line, 2);
+        assert_eq!(error.message, "expected a row of 3 cells, found 4");
+        assert!(read_generator(&b""[..]).is_err());
+    }
+
+    #[test]
+    fn leftward_slopes_wrap() {
+        let input = generator(EXAMPLE).unwrap();
+        let mirrored = generator(
+            &EXAMPLE
+                .lines()
+                .map(|line| line.chars().rev().collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+        .unwrap();
+        let right_edge = Point2::new(10, 0);
+        for &slope in &PUZZLE_SLOPES {
+            let left = Slope::new(-slope.dx(), slope.dy()).unwrap();
+            assert_eq!(
+                mirrored.trees_from(right_edge, left),
+                input.trees_from(Point2::ORIGIN, slope),
+                "{}",
+                left
+            );
+        }
+        assert_eq!(
+            input.trees_from(Point2::new(-11 * 5, 0), Slope::new(-3 - 11 * 7, 1).unwrap()),
+            input.trees_from(Point2::ORIGIN, Slope::new(8, 1).unwrap())
+        );
+        assert_eq!(Slope::new(-2, 1).unwrap().to_string(), "left 2, down 1");
+        assert_eq!(Slope::new(1, 0), None);
+    }
+
+    #[test]
+    fn paths_start_anywhere() {
+        let input = generator(EXAMPLE).unwrap();
+        let slope = Slope::new(3, 1).unwrap();
+        // Two steps before the origin lead onto the usual path.
+        assert_eq!(input.trees_from(Point2::new(-6, -2), slope), 7);
+        assert_eq!(input.trees_from(Point2::new(3, 1), slope), 7);
+        assert_eq!(input.trees_from(Point2::new(0, 11), slope), 0);
+        let far_above = Point2::new(0, i64::MIN);
+        let landing = Point2::new((-(i64::MIN as i128)).rem_euclid(11) as i64, 0);
+        let diagonal = Slope::new(1, 1).unwrap();
+        assert_eq!(
+            input.trees_from(far_above, diagonal),
+            input.trees_from(landing, diagonal)
+        );
+        assert_eq!(input.trees_on_slope(3, usize::MAX), 0);
+        assert_eq!(
+            input.trees_from(Point2::new(i64::MIN, -7), Slope::new(i64::MAX, 3).unwrap()),
+            input.trees_from(
+                Point2::new(
+                    (i64::MIN as i128 + 3 * i64::MAX as i128).rem_euclid(11) as i64,
+                    2
+                ),
+                Slope::new(i64::MAX.rem_euclid(11), 3).unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn search_finds_fewest_and_most_trees() {
+        let input = generator(EXAMPLE).unwrap();
+        let search = |goal| input.search_slopes(Point2::ORIGIN, -20..=20, 1..=3, goal);
+
+        let (slope, most) = search(Goal::Most).unwrap();
+        let (fewest_slope, fewest) = search(Goal::Fewest).unwrap();
+        let mut counts = Vec::new();
+        for dy in 1..=3 {
+            for dx in -20..=20 {
+                let slope = Slope::new(dx, dy).unwrap();
+                counts.push((slope, input.trees_from(Point2::ORIGIN, slope)));
+            }
+        }
+        assert_eq!(most, counts.iter().map(|&(_, n)| n).max().unwrap());
+        assert_eq!(fewest, counts.iter().map(|&(_, n)| n).min().unwrap());
+        assert_eq!(counts.iter().find(|&&(_, n)| n == most).unwrap().0, slope);
+        assert_eq!(
+            counts.iter().find(|&&(_, n)| n == fewest).unwrap().0,
+            fewest_slope
+        );
+
+        assert_eq!(
+            input.search_slopes(Point2::ORIGIN, 0..=5, -3..=0, Goal::Most),
+            None
+        );
+    }
+
+    #[test]
+    fn search_cuts_off_steep_slopes() {
+        let input = generator(EXAMPLE).unwrap();
+        for &start in &[Point2::new(2, 0), Point2::new(3, 4), Point2::new(0, -5)] {
+            for &goal in &[Goal::Most, Goal::Fewest] {
+                assert_eq!(
+                    input.search_slopes(start, -5..=i64::MAX, 1..=i64::MAX, goal),
+                    input.search_slopes(start, -5..=5, 1..=20, goal),
+                );
+            }
+        }
+        assert_eq!(
+            input.search_slopes(Point2::ORIGIN, 0..=0, 100..=i64::MAX, Goal::Fewest),
+            Some((Slope::new(0, 100).unwrap(), 0))
+        );
+    }
 }
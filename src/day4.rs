@@ -2,6 +2,14 @@ use std::collections::{HashMap, HashSet};
 
 use lazy_static::lazy_static;
 
+use crate::{
+    parse::{self, ParseError},
+    trace::event,
+    Puzzle,
+};
+
+const DAY: u8 = 4;
+
 type Passport = HashMap<String, String>;
 
 lazy_static! {
@@ -47,8 +55,8 @@ fn part2_validator(passport: &Passport) -> bool {
                         let (amount, unit) = value.split_at(index);
                         let amount = amount.parse::<u16>();
                         match (unit, amount) {
-                            ("cm", Ok(v)) => v >= 150 && v <= 193,
-                            ("in", Ok(v)) => v >= 59 && v <= 76,
+                            ("cm", Ok(v)) => (150..=193).contains(&v),
+                            ("in", Ok(v)) => (59..=76).contains(&v),
                             _ => false,
                         }
                     } else {
@@ -83,34 +91,139 @@ where
 {
     passports
         .iter()
-        .filter(|passport| validator(passport))
+        .enumerate()
+        .filter(|(i, passport)| {
+            let valid = validator(passport);
+            event!(DAY, "passport", passport = *i, valid = valid as u8);
+            valid
+        })
         .count()
 }
 
 #[aoc_generator(day4)]
-fn generator(input: &str) -> Vec<Passport> {
-    input
-        .split("\n\n")
-        .map(|row| {
-            row.trim()
-                .split_whitespace()
-                .fold(Passport::new(), |mut acc, part| {
-                    let mut values = part.split(':');
-                    let key = values.next().unwrap().to_string();
-                    let value = values.next().unwrap().to_string();
-                    acc.insert(key, value);
-                    acc
-                })
+fn generator(input: &str) -> Result<Vec<Passport>, ParseError> {
+    let input = &parse::normalize(input);
+    parse::paragraphs(input)
+        .map(|(offset, paragraph)| {
+            let fields = parse::lines(paragraph, |line| {
+                line.split_whitespace()
+                    .map(|part| {
+                        let mut values = part.splitn(2, ':');
+                        let key = values.next().unwrap_or_default().to_string();
+                        let value = values
+                            .next()
+                            .ok_or_else(|| ParseError::at(DAY, line, part, "expected `key:value`"))?
+                            .to_string();
+                        Ok((key, value))
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(|e| e.offset_lines(offset))?;
+
+            Ok(fields.into_iter().flatten().collect())
         })
         .collect()
 }
 
 #[aoc(day4, part1)]
 fn part1(passports: &[Passport]) -> usize {
-    solve(&passports, part1_validator)
+    solve(passports, part1_validator)
 }
 
 #[aoc(day4, part2)]
 fn part2(passports: &[Passport]) -> usize {
-    solve(&passports, part2_validator)
+    solve(passports, part2_validator)
+}
+
+pub struct Day4;
+
+impl Puzzle for Day4 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = DAY;
+
+    type Input = Vec<Passport>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part1_example() {
+        let input = generator(
+            "\
+ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
+hcl:#cfa07d byr:1929
+
+hcl:#ae17e1 iyr:2013
+eyr:2024
+ecl:brn pid:760753108 byr:1931
+hgt:179cm
+
+hcl:#cfa07d eyr:2025 pid:166559648
+iyr:2011 ecl:brn hgt:59in",
+        )
+        .unwrap();
+        assert_eq!(part1(&input), 2);
+    }
+
+    #[test]
+    fn part2_invalid_examples() {
+        let input = generator(
+            "\
+eyr:1972 cid:100
+hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
+
+iyr:2019
+hcl:#602927 eyr:1967 hgt:170cm
+ecl:grn pid:012533040 byr:1946
+
+hcl:dab227 iyr:2012
+ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277
+
+hgt:59cm ecl:zzz
+eyr:2038 hcl:74454a iyr:2023
+pid:3556412378 byr:2007",
+        )
+        .unwrap();
+        assert_eq!(part2(&input), 0);
+    }
+
+    #[test]
+    fn part2_valid_examples() {
+        let input = generator(
+            "\
+pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f
+
+eyr:2029 ecl:blu cid:129 byr:1989
+iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm
+
+hcl:#888785
+hgt:164cm byr:2001 iyr:2015 cid:88
+pid:545766238 ecl:hzl
+eyr:2022
+
+iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719",
+        )
+        .unwrap();
+        assert_eq!(part2(&input), 4);
+    }
 }
@@ -1,38 +1,53 @@
-use std::{num::ParseIntError, str::FromStr};
+use std::str::FromStr;
+
+use crate::{
+    parse::{self, ParseError},
+    trace::event,
+    Puzzle,
+};
+
+const DAY: u8 = 5;
 
 #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
-struct BoardingPass(u16);
+pub struct BoardingPass(u16);
 
 impl FromStr for BoardingPass {
-    type Err = ParseIntError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let id = s
-            .chars()
-            .map(|ch| match ch {
-                'F' => '0',
-                'B' => '1',
-                'L' => '0',
-                'R' => '1',
-                _ => unreachable!(),
+            .char_indices()
+            .map(|(i, ch)| match ch {
+                'F' => Ok('0'),
+                'B' => Ok('1'),
+                'L' => Ok('0'),
+                'R' => Ok('1'),
+                _ => Err(parse::unexpected(DAY, s, i, "expected one of `FBLR`")),
             })
-            .collect::<String>();
-        u16::from_str_radix(&id, 2).map(BoardingPass)
+            .collect::<Result<String, _>>()?;
+        u16::from_str_radix(&id, 2)
+            .map(BoardingPass)
+            .map_err(|e| ParseError::new(DAY, s, e))
     }
 }
 
 #[aoc_generator(day5)]
-fn generator(input: &str) -> Vec<BoardingPass> {
-    input
-        .lines()
-        .map(str::parse)
-        .collect::<Result<Vec<_>, _>>()
-        .unwrap()
+fn generator(input: &str) -> Result<Vec<BoardingPass>, ParseError> {
+    let input = &parse::normalize(input);
+    parse::lines(input, str::parse)
 }
 
 #[aoc(day5, part1)]
 fn part1(passes: &[BoardingPass]) -> u16 {
-    passes.iter().max().map(|&BoardingPass(n)| n).unwrap()
+    let highest = passes.iter().max().map(|&BoardingPass(n)| n).unwrap();
+    event!(
+        DAY,
+        "highest",
+        seat = highest,
+        row = highest >> 3,
+        column = highest & 7
+    );
+    highest
 }
 
 #[aoc(day5, part2)]
@@ -49,6 +64,60 @@ fn part2(passes: &[BoardingPass]) -> u16 {
         })
         .map(|passes| passes[0].0)
         .unwrap();
+    event!(DAY, "gap", before = neighbors, after = neighbors + 2);
 
     neighbors + 1
 }
+
+pub struct Day5;
+
+impl Puzzle for Day5 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = DAY;
+
+    type Input = Vec<BoardingPass>;
+    type Part1 = u16;
+    type Part2 = u16;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seat_ids() {
+        let examples = [
+            ("FBFBBFFRLR", 357),
+            ("BFFFBBFRRR", 567),
+            ("FFFBBBFRRR", 119),
+            ("BBFFBBFRLL", 820),
+        ];
+        for &(pass, id) in &examples {
+            assert_eq!(pass.parse::<BoardingPass>().unwrap().0, id);
+        }
+    }
+
+    #[test]
+    fn part1_example() {
+        let input = generator("FBFBBFFRLR\nBFFFBBFRRR\nFFFBBBFRRR\nBBFFBBFRLL").unwrap();
+        assert_eq!(part1(&input), 820);
+    }
+
+    #[test]
+    fn part2_finds_gap() {
+        let input = generator("FBFBBFFRLL\nFBFBBFFRRL\nFBFBBFFRRR").unwrap();
+        assert_eq!(part2(&input), 357);
+    }
+}
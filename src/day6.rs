@@ -2,11 +2,34 @@ use std::collections::HashSet;
 
 use itertools::Itertools;
 
+use crate::{
+    parse::{self, ParseError},
+    trace::event,
+    Puzzle,
+};
+
+const DAY: u8 = 6;
+
 #[aoc_generator(day6)]
-fn generator(input: &str) -> Vec<Vec<HashSet<char>>> {
-    input
-        .split("\n\n")
-        .map(|group| group.lines().map(|line| line.chars().collect()).collect())
+fn generator(input: &str) -> Result<Vec<Vec<HashSet<char>>>, ParseError> {
+    let input = &parse::normalize(input);
+    parse::paragraphs(input)
+        .map(|(offset, group)| {
+            parse::lines(group, |line| {
+                line.char_indices()
+                    .map(|(i, ch)| match ch {
+                        'a'..='z' => Ok(ch),
+                        _ => Err(parse::unexpected(
+                            DAY,
+                            line,
+                            i,
+                            "expected a question `a`-`z`",
+                        )),
+                    })
+                    .collect()
+            })
+            .map_err(|e| e.offset_lines(offset))
+        })
         .collect()
 }
 
@@ -14,7 +37,18 @@ fn generator(input: &str) -> Vec<Vec<HashSet<char>>> {
 fn part1(answers: &[Vec<HashSet<char>>]) -> usize {
     answers
         .iter()
-        .map(|group| group.iter().flatten().unique().count())
+        .enumerate()
+        .map(|(i, group)| {
+            let count = group.iter().flatten().unique().count();
+            event!(
+                DAY,
+                "group",
+                group = i,
+                people = group.len(),
+                anyone = count
+            );
+            count
+        })
         .sum()
 }
 
@@ -22,10 +56,61 @@ fn part1(answers: &[Vec<HashSet<char>>]) -> usize {
 fn part2(answers: &[Vec<HashSet<char>>]) -> usize {
     answers
         .iter()
-        .map(|group| {
-            ('a'..='z')
+        .enumerate()
+        .map(|(i, group)| {
+            let count = ('a'..='z')
                 .filter(|c| group.iter().all(|a| a.contains(c)))
-                .count()
+                .count();
+            event!(
+                DAY,
+                "group",
+                group = i,
+                people = group.len(),
+                everyone = count
+            );
+            count
         })
         .sum()
 }
+
+pub struct Day6;
+
+impl Puzzle for Day6 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = DAY;
+
+    type Input = Vec<Vec<HashSet<char>>>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "abc\n\na\nb\nc\n\nab\nac\n\na\na\na\na\n\nb";
+
+    #[test]
+    fn part1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 11);
+    }
+
+    #[test]
+    fn part2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part2(&input), 6);
+    }
+}
@@ -2,36 +2,43 @@ use std::collections::{HashMap, VecDeque};
 
 use regex::Regex;
 
+use crate::{
+    parse::{self, ParseError},
+    trace::event,
+    Puzzle,
+};
+
+const DAY: u8 = 7;
 const SEARCH: &str = "shiny gold";
 
-struct Rule {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rule {
     count: usize,
     name: String,
 }
 
 #[aoc_generator(day7)]
-fn generator(input: &str) -> HashMap<String, Vec<Rule>> {
+fn generator(input: &str) -> Result<HashMap<String, Vec<Rule>>, ParseError> {
+    let input = &parse::normalize(input);
     let name_re = Regex::new(r"^(.+) bags contain").unwrap();
     let rule_re = Regex::new(r"(\d+) (.+?) bags?").unwrap();
-    input
-        .lines()
-        .map(|line| {
-            let name_match = name_re.captures(line).unwrap();
-            let name = name_match.get(1).map(|m| m.as_str().to_string()).unwrap();
-            let rules = rule_re
-                .captures_iter(line)
-                .map(|rule_capture| {
-                    let count = rule_capture
-                        .get(1)
-                        .and_then(|m| m.as_str().parse().ok())
-                        .unwrap();
-                    let name = rule_capture.get(2).map(|m| m.as_str().to_string()).unwrap();
-                    Rule { count, name }
-                })
-                .collect();
-            (name, rules)
-        })
-        .collect()
+    let rules = parse::lines(input, |line| {
+        let name_match = name_re
+            .captures(line)
+            .ok_or_else(|| ParseError::new(DAY, line, "expected `<color> bags contain`"))?;
+        let name = name_match[1].to_string();
+        let rules = rule_re
+            .captures_iter(line)
+            .map(|rule_capture| {
+                let count = parse::field(DAY, line, &rule_capture[1])?;
+                let name = rule_capture[2].to_string();
+                Ok(Rule { count, name })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok((name, rules))
+    })?;
+
+    Ok(rules.into_iter().collect())
 }
 
 #[aoc(day7, part1)]
@@ -50,6 +57,7 @@ fn part1(rules: &HashMap<String, Vec<Rule>>) -> usize {
                     .map(|r| r.name.as_str())
                     .collect::<Vec<_>>();
                 if children.contains(&SEARCH) {
+                    event!(DAY, "holds", bag = name, via = entry);
                     return true;
                 } else {
                     search_queue.extend(children);
@@ -70,6 +78,13 @@ fn part2(rules: &HashMap<String, Vec<Rule>>) -> usize {
     while let Some((n, entry)) = search_queue.pop_front() {
         for rule in &rules[entry] {
             let weight = n * rule.count;
+            event!(
+                DAY,
+                "inside",
+                bag = entry,
+                holds = rule.name.as_str(),
+                count = weight
+            );
             count += weight;
             search_queue.push_back((weight, rule.name.as_str()));
         }
@@ -77,3 +92,70 @@ fn part2(rules: &HashMap<String, Vec<Rule>>) -> usize {
 
     count
 }
+
+pub struct Day7;
+
+impl Puzzle for Day7 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = DAY;
+
+    type Input = HashMap<String, Vec<Rule>>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+light red bags contain 1 bright white bag, 2 muted yellow bags.
+dark orange bags contain 3 bright white bags, 4 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
+shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
+dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+faded blue bags contain no other bags.
+dotted black bags contain no other bags.";
+
+    #[test]
+    fn part1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 4);
+    }
+
+    #[test]
+    fn part2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part2(&input), 32);
+    }
+
+    #[test]
+    fn part2_deep_example() {
+        let input = generator(
+            "\
+shiny gold bags contain 2 dark red bags.
+dark red bags contain 2 dark orange bags.
+dark orange bags contain 2 dark yellow bags.
+dark yellow bags contain 2 dark green bags.
+dark green bags contain 2 dark blue bags.
+dark blue bags contain 2 dark violet bags.
+dark violet bags contain no other bags.",
+        )
+        .unwrap();
+        assert_eq!(part2(&input), 126);
+    }
+}
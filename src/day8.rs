@@ -1,4 +1,14 @@
-use std::{collections::HashSet, num::ParseIntError, str::FromStr};
+use std::{collections::HashSet, convert::TryFrom, io::BufRead, str::FromStr};
+
+use rayon::prelude::*;
+
+use crate::{
+    parse::{self, ParseError},
+    trace::event,
+    Puzzle,
+};
+
+const DAY: u8 = 8;
 
 #[derive(Copy, Clone, Default)]
 struct Environment {
@@ -7,6 +17,7 @@ struct Environment {
 }
 
 #[derive(Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Instruction {
     Increment,
     NoOperation,
@@ -14,53 +25,81 @@ enum Instruction {
 }
 
 impl FromStr for Instruction {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let instruction = match s {
             "acc" => Instruction::Increment,
             "nop" => Instruction::NoOperation,
             "jmp" => Instruction::Jump,
-            _ => unreachable!(),
+            _ => return Err(ParseError::new(DAY, s, "expected `acc`, `nop` or `jmp`")),
         };
 
         Ok(instruction)
     }
 }
 
+impl Instruction {
+    /// The instruction that repairs a corrupted one, if it can be repaired.
+    fn flipped(self) -> Option<Self> {
+        match self {
+            Instruction::Increment => None,
+            Instruction::NoOperation => Some(Instruction::Jump),
+            Instruction::Jump => Some(Instruction::NoOperation),
+        }
+    }
+
+    fn mnemonic(self) -> &'static str {
+        match self {
+            Instruction::Increment => "acc",
+            Instruction::NoOperation => "nop",
+            Instruction::Jump => "jmp",
+        }
+    }
+}
+
 #[derive(Copy, Clone)]
-struct Operation {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Operation {
     instruction: Instruction,
-    argument: i16,
+    argument: i64,
 }
 
 impl Operation {
-    fn step(&self, mut env: Environment) -> Environment {
-        match self.instruction {
+    /// Runs the operation, returning `None` if it jumps to before the first
+    /// instruction.
+    fn step(&self, mut env: Environment) -> Option<Environment> {
+        let offset = match self.instruction {
             Instruction::Increment => {
-                env.accumulator += self.argument as i64;
-            }
-            Instruction::NoOperation => {}
-            Instruction::Jump => {
-                env.ip = (env.ip as i16 + self.argument) as usize;
+                env.accumulator += self.argument;
+                1
             }
+            Instruction::NoOperation => 1,
+            Instruction::Jump => self.argument,
         };
 
-        if self.instruction != Instruction::Jump {
-            env.ip += 1;
-        }
+        env.ip = i64::try_from(env.ip)
+            .ok()
+            .and_then(|ip| ip.checked_add(offset))
+            .and_then(|ip| usize::try_from(ip).ok())?;
 
-        env
+        Some(env)
     }
 }
 
 impl FromStr for Operation {
-    type Err = ParseIntError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut parts = s.split_whitespace();
-        let instruction = parts.next().and_then(|i| i.parse().ok()).unwrap();
-        let argument = parts.next().map(|i| i.parse()).unwrap()?;
+        let instruction = parts
+            .next()
+            .ok_or_else(|| ParseError::new(DAY, s, "missing instruction"))
+            .and_then(|i| i.parse().map_err(|e: ParseError| e.relative_to(s, i)))?;
+        let argument = parts
+            .next()
+            .ok_or_else(|| ParseError::new(DAY, s, "missing argument"))
+            .and_then(|i| parse::field(DAY, s, i))?;
 
         let operation = Operation {
             instruction,
@@ -72,12 +111,13 @@ impl FromStr for Operation {
 }
 
 #[aoc_generator(day8)]
-fn generator(input: &str) -> Vec<Operation> {
-    input
-        .lines()
-        .map(str::parse)
-        .collect::<Result<_, _>>()
-        .unwrap()
+fn generator(input: &str) -> Result<Vec<Operation>, ParseError> {
+    let input = &parse::normalize(input);
+    parse::lines(input, str::parse)
+}
+
+fn read_generator<R: BufRead>(reader: R) -> Result<Vec<Operation>, ParseError> {
+    parse::read_lines(DAY, reader, str::parse)
 }
 
 #[aoc(day8, part1)]
@@ -86,13 +126,28 @@ fn part1(operations: &[Operation]) -> i64 {
     let mut seen = HashSet::new();
     loop {
         if seen.contains(&env.ip) {
+            event!(DAY, "repeat", ip = env.ip, accumulator = env.accumulator);
             break;
         } else {
             seen.insert(env.ip);
         }
 
-        let op = operations[env.ip];
-        env = op.step(env);
+        let op = match operations.get(env.ip) {
+            Some(&op) => op,
+            None => break,
+        };
+        env = match op.step(env) {
+            Some(env) => env,
+            None => break,
+        };
+        event!(
+            DAY,
+            "execute",
+            op = op.instruction.mnemonic(),
+            argument = op.argument,
+            ip = env.ip,
+            accumulator = env.accumulator,
+        );
     }
 
     env.accumulator
@@ -100,39 +155,106 @@ fn part1(operations: &[Operation]) -> i64 {
 
 #[aoc(day8, part2)]
 fn part2(operations: &[Operation]) -> i64 {
-    let candidates = (0..operations.len())
-        .filter_map(|n| {
-            let op = operations[n];
-            let instruction = match op.instruction {
-                Instruction::Increment => return None,
-                Instruction::NoOperation => Instruction::Jump,
-                Instruction::Jump => Instruction::NoOperation,
-            };
-
-            let mut candidate = operations.to_vec();
-            candidate[n].instruction = instruction;
-
-            Some(candidate)
-        })
-        .collect::<Vec<Vec<Operation>>>();
-
-    for operations in candidates {
-        let mut env = Environment::default();
-        let mut count = vec![0u8; operations.len()];
-        loop {
-            count[env.ip] += 1;
-            if count[env.ip] > 25 {
-                break;
-            }
+    (0..operations.len())
+        .into_par_iter()
+        .filter_map(|n| operations[n].instruction.flipped().map(|flip| (n, flip)))
+        .find_map_first(|(n, flip)| {
+            let mut env = Environment::default();
+            let mut count = vec![0u8; operations.len()];
+            loop {
+                count[env.ip] += 1;
+                if count[env.ip] > 25 {
+                    event!(DAY, "loops", flipped = n, ip = env.ip);
+                    return None;
+                }
 
-            let op = operations[env.ip];
-            env = op.step(env);
+                // Run the shared program as if instruction `n` were flipped,
+                // rather than copying it for every candidate.
+                let mut op = operations[env.ip];
+                if env.ip == n {
+                    op.instruction = flip;
+                }
+                env = op.step(env)?;
 
-            if env.ip >= operations.len() {
-                return env.accumulator;
+                if env.ip >= operations.len() {
+                    event!(
+                        DAY,
+                        "terminates",
+                        flipped = n,
+                        accumulator = env.accumulator
+                    );
+                    return Some(env.accumulator);
+                }
             }
-        }
+        })
+        .unwrap()
+}
+
+pub struct Day8;
+
+impl Puzzle for Day8 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = DAY;
+
+    type Input = Vec<Operation>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        generator(input)
+    }
+
+    fn parse_reader<R: BufRead>(reader: R) -> Result<Self::Input, ParseError> {
+        read_generator(reader)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
     }
 
-    unreachable!()
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6";
+
+    #[test]
+    fn part1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 5);
+    }
+
+    #[test]
+    fn part2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part2(&input), 8);
+    }
+
+    #[test]
+    fn long_programs() {
+        // Jumps further than an `i16` can reach, over a program too long to
+        // index with one.
+        let len = 40_000;
+        let mut program = vec!["nop +0".to_string(); len];
+        program[0] = format!("jmp +{}", len - 2);
+        program[len - 2] = "acc +5".to_string();
+        program[len - 1] = format!("jmp -{}", len - 1);
+        let input = read_generator(program.join("\n").as_bytes()).unwrap();
+        assert_eq!(part1(&input), 5);
+        assert_eq!(part2(&input), 5);
+    }
 }
@@ -1,43 +1,110 @@
+use std::io::BufRead;
+
 use itertools::Itertools;
 
+use crate::{
+    parse::{self, ParseError},
+    trace::event,
+    Puzzle,
+};
+
+const DAY: u8 = 9;
 const PREAMBLE_SIZE: usize = 25;
 
 #[aoc_generator(day9)]
-fn generator(input: &str) -> Vec<u64> {
-    input
-        .lines()
-        .map(str::parse)
-        .collect::<Result<_, _>>()
-        .unwrap()
+fn generator(input: &str) -> Result<Vec<u64>, ParseError> {
+    let input = &parse::normalize(input);
+    parse::lines(input, |line| parse::field(DAY, line, line))
 }
 
-#[aoc(day9, part1)]
-fn part1(input: &[u64]) -> u64 {
+fn read_generator<R: BufRead>(reader: R) -> Result<Vec<u64>, ParseError> {
+    parse::read_lines(DAY, reader, |line| parse::field(DAY, line, line))
+}
+
+fn find_invalid(input: &[u64], preamble_size: usize) -> u64 {
     input
-        .windows(PREAMBLE_SIZE + 1)
+        .windows(preamble_size + 1)
         .find_map(|window| {
-            let sum = window[PREAMBLE_SIZE];
-            let found = window[0..PREAMBLE_SIZE]
+            let sum = window[preamble_size];
+            let found = window[0..preamble_size]
                 .iter()
                 .combinations(2)
                 .any(|numbers| numbers.iter().copied().sum::<u64>() == sum);
             if found {
                 None
             } else {
+                event!(DAY, "invalid", number = sum);
                 Some(sum)
             }
         })
         .unwrap()
 }
 
-#[aoc(day9, part2)]
-fn part2(input: &[u64]) -> u64 {
-    let sum = part1(input);
+fn find_weakness(input: &[u64], preamble_size: usize) -> u64 {
+    let sum = find_invalid(input, preamble_size);
     let answer = (2..input.len())
         .map(|n| input.windows(n))
         .find_map(|mut windows| windows.find(|window| window.iter().copied().sum::<u64>() == sum))
         .unwrap();
 
     let (min, max) = answer.iter().copied().minmax().into_option().unwrap();
+    event!(DAY, "weakness", length = answer.len(), min = min, max = max);
     min + max
 }
+
+#[aoc(day9, part1)]
+fn part1(input: &[u64]) -> u64 {
+    find_invalid(input, PREAMBLE_SIZE)
+}
+
+#[aoc(day9, part2)]
+fn part2(input: &[u64]) -> u64 {
+    find_weakness(input, PREAMBLE_SIZE)
+}
+
+pub struct Day9;
+
+impl Puzzle for Day9 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = DAY;
+
+    type Input = Vec<u64>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        generator(input)
+    }
+
+    fn parse_reader<R: BufRead>(reader: R) -> Result<Self::Input, ParseError> {
+        read_generator(reader)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+35\n20\n15\n25\n47\n40\n62\n55\n65\n95\n102\n117\n150\n182\n127\n219\n299\n277\n309\n576";
+
+    #[test]
+    fn part1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(find_invalid(&input, 5), 127);
+    }
+
+    #[test]
+    fn part2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(find_weakness(&input, 5), 62);
+    }
+}
@@ -0,0 +1,124 @@
+use std::{
+    error::Error,
+    fmt,
+    ops::{Range, RangeInclusive},
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::{
+    oracle, random,
+    registry::{Part, Solver},
+};
+
+/// An input on which a solver disagrees with the [`oracle`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub year: u16,
+    pub day: u8,
+    pub part: Part,
+    pub input: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} day {} part {}: expected {}, got {} for input:",
+            self.year, self.day, self.part, self.expected, self.actual
+        )?;
+        write!(f, "{}", self.input)
+    }
+}
+
+impl Error for Mismatch {}
+
+/// Solves one part of `input` and checks it against the oracle. Inputs the
+/// oracle has no answer for are skipped, so `None` means either agreement
+/// or nothing to compare.
+pub fn compare(solver: &dyn Solver, input: &str, part: Part) -> Option<Mismatch> {
+    let expected = oracle::solve(solver.year(), solver.day(), input, part)?;
+    check(solver, input, part, expected)
+}
+
+fn check(solver: &dyn Solver, input: &str, part: Part, expected: String) -> Option<Mismatch> {
+    let actual = match panic::catch_unwind(AssertUnwindSafe(|| solver.run(input, part))) {
+        Ok(Ok(answer)) => answer,
+        Ok(Err(e)) => format!("an error ({})", e),
+        Err(_) => "a panic".to_string(),
+    };
+
+    if actual == expected {
+        return None;
+    }
+
+    Some(Mismatch {
+        year: solver.year(),
+        day: solver.day(),
+        part,
+        input: input.to_string(),
+        expected,
+        actual,
+    })
+}
+
+/// Drops lines from the input one at a time for as long as the solver
+/// still disagrees with the oracle on what is left.
+fn shrink(solver: &dyn Solver, mut mismatch: Mismatch) -> Mismatch {
+    loop {
+        let smaller = {
+            let lines = mismatch.input.lines().collect::<Vec<_>>();
+            (0..lines.len()).find_map(|skip| {
+                let input = lines
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, _)| i != skip)
+                    .map(|(_, line)| *line)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                compare(solver, &input, mismatch.part)
+            })
+        };
+
+        match smaller {
+            Some(smaller) => mismatch = smaller,
+            None => return mismatch,
+        }
+    }
+}
+
+/// Compares `parts` of a day against the oracle on random inputs of every
+/// size in `sizes`, smallest first, for each seed.
+///
+/// Returns how many answers were compared, or the first disagreement with
+/// its input shrunk to as few lines as still show it.
+pub fn run(
+    solver: &dyn Solver,
+    parts: &[Part],
+    sizes: RangeInclusive<usize>,
+    seeds: Range<u64>,
+) -> Result<usize, Mismatch> {
+    let mut compared = 0;
+    for size in sizes {
+        for seed in seeds.clone() {
+            let input = match random::input(solver.year(), solver.day(), size, seed) {
+                Some(input) => input,
+                None => continue,
+            };
+
+            for &part in parts {
+                let expected = match oracle::solve(solver.year(), solver.day(), &input, part) {
+                    Some(expected) => expected,
+                    None => continue,
+                };
+                if let Some(mismatch) = check(solver, &input, part, expected) {
+                    return Err(shrink(solver, mismatch));
+                }
+                compared += 1;
+            }
+        }
+    }
+
+    Ok(compared)
+}
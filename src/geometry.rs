@@ -0,0 +1,346 @@
+use std::{
+    fmt, iter,
+    ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign},
+};
+
+/// An integer point, or vector, in `N` dimensions.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point<const N: usize>(pub [i64; N]);
+
+pub type Point2 = Point<2>;
+pub type Point3 = Point<3>;
+pub type Point4 = Point<4>;
+
+impl<const N: usize> Point<N> {
+    pub const ORIGIN: Self = Point([0; N]);
+
+    /// A point with every coordinate set to `value`.
+    pub fn splat(value: i64) -> Self {
+        Point([value; N])
+    }
+
+    fn map<F>(self, f: F) -> Self
+    where
+        F: Fn(i64) -> i64,
+    {
+        let mut coordinates = self.0;
+        for c in coordinates.iter_mut() {
+            *c = f(*c);
+        }
+        Point(coordinates)
+    }
+
+    fn zip_with<F>(self, other: Self, f: F) -> Self
+    where
+        F: Fn(i64, i64) -> i64,
+    {
+        let mut coordinates = self.0;
+        for (a, &b) in coordinates.iter_mut().zip(other.0.iter()) {
+            *a = f(*a, b);
+        }
+        Point(coordinates)
+    }
+
+    pub fn min(self, other: Self) -> Self {
+        self.zip_with(other, i64::min)
+    }
+
+    pub fn max(self, other: Self) -> Self {
+        self.zip_with(other, i64::max)
+    }
+
+    /// Manhattan length of this vector.
+    pub fn manhattan(self) -> i64 {
+        self.0.iter().map(|c| c.abs()).sum()
+    }
+
+    /// Chebyshev length of this vector, the number of king moves to reach it.
+    pub fn chebyshev(self) -> i64 {
+        self.0.iter().map(|c| c.abs()).max().unwrap_or(0)
+    }
+
+    pub fn manhattan_distance(self, other: Self) -> i64 {
+        (self - other).manhattan()
+    }
+
+    pub fn chebyshev_distance(self, other: Self) -> i64 {
+        (self - other).chebyshev()
+    }
+
+    /// Every offset with a Chebyshev length of one, 3^N - 1 in all.
+    pub fn neighborhood() -> impl Iterator<Item = Self> {
+        let count = 3usize.pow(N as u32);
+        (0..count)
+            .map(|mut n| {
+                let mut offset = [0; N];
+                for c in offset.iter_mut() {
+                    *c = (n % 3) as i64 - 1;
+                    n /= 3;
+                }
+                Point(offset)
+            })
+            .filter(|&offset| offset != Self::ORIGIN)
+    }
+
+    /// Every offset with a Manhattan length of one, 2N in all.
+    pub fn orthogonal_neighborhood() -> impl Iterator<Item = Self> {
+        (0..N).flat_map(|axis| {
+            [-1, 1].iter().map(move |&step| {
+                let mut offset = [0; N];
+                offset[axis] = step;
+                Point(offset)
+            })
+        })
+    }
+
+    /// The points surrounding this one, orthogonally and diagonally.
+    pub fn neighbors(self) -> impl Iterator<Item = Self> {
+        Self::neighborhood().map(move |offset| self + offset)
+    }
+
+    /// The points sharing a face with this one.
+    pub fn orthogonal_neighbors(self) -> impl Iterator<Item = Self> {
+        Self::orthogonal_neighborhood().map(move |offset| self + offset)
+    }
+
+    /// The smallest box holding every point, as its minimum and maximum
+    /// corners, or `None` when there are no points.
+    pub fn bounds<I>(points: I) -> Option<(Self, Self)>
+    where
+        I: IntoIterator<Item = Self>,
+    {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold((first, first), |(min, max), p| (min.min(p), max.max(p))))
+    }
+
+    /// Every point in the box between `min` and `max`, inclusive.
+    pub fn cuboid(min: Self, max: Self) -> impl Iterator<Item = Self> {
+        let empty = min.0.iter().zip(max.0.iter()).any(|(a, b)| a > b);
+        let mut next = if empty { None } else { Some(min) };
+        iter::from_fn(move || {
+            let current = next?;
+            let mut following = current;
+            next = None;
+            for axis in 0..N {
+                if following[axis] < max[axis] {
+                    following[axis] += 1;
+                    next = Some(following);
+                    break;
+                }
+                following[axis] = min[axis];
+            }
+            Some(current)
+        })
+    }
+}
+
+impl Point2 {
+    pub const fn new(x: i64, y: i64) -> Self {
+        Point([x, y])
+    }
+
+    pub fn x(self) -> i64 {
+        self.0[0]
+    }
+
+    pub fn y(self) -> i64 {
+        self.0[1]
+    }
+
+    /// Turns a quarter turn anticlockwise, with `y` pointing up.
+    pub fn rotate_left(self) -> Self {
+        Point2::new(-self.y(), self.x())
+    }
+
+    /// Turns a quarter turn clockwise, with `y` pointing up.
+    pub fn rotate_right(self) -> Self {
+        Point2::new(self.y(), -self.x())
+    }
+
+    /// Turns anticlockwise by `quarter_turns`, clockwise when negative.
+    pub fn rotate(self, quarter_turns: i64) -> Self {
+        match quarter_turns.rem_euclid(4) {
+            0 => self,
+            1 => self.rotate_left(),
+            2 => -self,
+            _ => self.rotate_right(),
+        }
+    }
+}
+
+impl<const N: usize> Default for Point<N> {
+    fn default() -> Self {
+        Self::ORIGIN
+    }
+}
+
+impl<const N: usize> From<[i64; N]> for Point<N> {
+    fn from(coordinates: [i64; N]) -> Self {
+        Point(coordinates)
+    }
+}
+
+impl<const N: usize> Index<usize> for Point<N> {
+    type Output = i64;
+
+    fn index(&self, axis: usize) -> &Self::Output {
+        &self.0[axis]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for Point<N> {
+    fn index_mut(&mut self, axis: usize) -> &mut Self::Output {
+        &mut self.0[axis]
+    }
+}
+
+impl<const N: usize> Add for Point<N> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.zip_with(rhs, |a, b| a + b)
+    }
+}
+
+impl<const N: usize> AddAssign for Point<N> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<const N: usize> Sub for Point<N> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.zip_with(rhs, |a, b| a - b)
+    }
+}
+
+impl<const N: usize> SubAssign for Point<N> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<const N: usize> Mul<i64> for Point<N> {
+    type Output = Self;
+
+    fn mul(self, rhs: i64) -> Self::Output {
+        self.map(|c| c * rhs)
+    }
+}
+
+impl<const N: usize> Neg for Point<N> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        self * -1
+    }
+}
+
+impl<const N: usize> fmt::Display for Point<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(")?;
+        for (i, c) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", c)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Points serialize as a tuple of their coordinates, `[x, y, ...]` in JSON.
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for Point<N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeTuple;
+
+        let mut tuple = serializer.serialize_tuple(N)?;
+        for c in self.0.iter() {
+            tuple.serialize_element(c)?;
+        }
+        tuple.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for Point<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{Error, SeqAccess, Visitor};
+
+        struct PointVisitor<const N: usize>;
+
+        impl<'de, const N: usize> Visitor<'de> for PointVisitor<N> {
+            type Value = Point<N>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a tuple of {} coordinates", N)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut coordinates = [0; N];
+                for (i, c) in coordinates.iter_mut().enumerate() {
+                    *c = seq
+                        .next_element()?
+                        .ok_or_else(|| A::Error::invalid_length(i, &self))?;
+                }
+                Ok(Point(coordinates))
+            }
+        }
+
+        deserializer.deserialize_tuple(N, PointVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let a = Point([1, -2, 3]);
+        let b = Point([4, 5, -6]);
+        assert_eq!(a + b, Point([5, 3, -3]));
+        assert_eq!(a - b, Point([-3, -7, 9]));
+        assert_eq!(a * 2, Point([2, -4, 6]));
+        assert_eq!(-a, Point([-1, 2, -3]));
+    }
+
+    #[test]
+    fn distances() {
+        let a = Point2::new(1, 1);
+        let b = Point2::new(4, -3);
+        assert_eq!(a.manhattan_distance(b), 7);
+        assert_eq!(a.chebyshev_distance(b), 4);
+    }
+
+    #[test]
+    fn rotations() {
+        let p = Point2::new(10, 4);
+        assert_eq!(p.rotate_left(), Point2::new(-4, 10));
+        assert_eq!(p.rotate_right(), Point2::new(4, -10));
+        assert_eq!(p.rotate(2), Point2::new(-10, -4));
+        assert_eq!(p.rotate(-1), p.rotate_right());
+        assert_eq!(p.rotate(4), p);
+    }
+
+    #[test]
+    fn neighborhoods() {
+        assert_eq!(Point2::neighborhood().count(), 8);
+        assert_eq!(Point4::neighborhood().count(), 80);
+        assert_eq!(Point3::orthogonal_neighborhood().count(), 6);
+        assert!(Point3::neighborhood().all(|offset| offset.chebyshev() == 1));
+    }
+
+    #[test]
+    fn boxes() {
+        let points = vec![Point2::new(2, -1), Point2::new(-1, 3), Point2::new(0, 0)];
+        let (min, max) = Point::bounds(points).unwrap();
+        assert_eq!((min, max), (Point2::new(-1, -1), Point2::new(2, 3)));
+        assert_eq!(Point::cuboid(min, max).count(), 20);
+        assert_eq!(Point::cuboid(max, min).count(), 0);
+    }
+}
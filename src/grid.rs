@@ -0,0 +1,319 @@
+use std::{
+    fmt, iter,
+    ops::{Index, IndexMut},
+};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{geometry::Point2, parse, ParseError};
+
+/// How a [`Grid`] answers for points beyond its stored cells.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Edge<T> {
+    /// Nothing exists past the edge.
+    Bounded,
+    /// The stored cells repeat forever in every direction.
+    Wrapping,
+    /// Every point past the edge holds the same value.
+    Infinite(T),
+}
+
+/// A dense, row-major rectangle of cells addressed by `(x, y)` points, with
+/// `y` growing downwards from the top-left corner.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+    edge: Edge<T>,
+}
+
+impl<T> Grid<T> {
+    /// Parses one row per line, mapping each character to a cell. The
+    /// mapping returns a message describing what it expected on failure.
+    pub fn parse<F>(day: u8, input: &str, mut f: F) -> Result<Self, ParseError>
+    where
+        F: FnMut(char) -> Result<T, &'static str>,
+    {
+        let rows = parse::lines(input, |line| {
+            line.char_indices()
+                .map(|(i, ch)| f(ch).map_err(|message| parse::unexpected(day, line, i, message)))
+                .collect::<Result<Vec<T>, _>>()
+        })?;
+
+        let width = rows.first().map_or(0, Vec::len);
+        if width == 0 {
+            return Err(ParseError::new(day, input, "empty grid"));
+        }
+
+        let height = rows.len();
+        let mut cells = Vec::with_capacity(width * height);
+        for (y, row) in rows.into_iter().enumerate() {
+            if row.len() != width {
+                let message = format!("expected a row of {} cells, found {}", width, row.len());
+                let line = input.lines().nth(y).unwrap_or_default();
+                return Err(ParseError::new(day, line, message).with_line(y + 1));
+            }
+            cells.extend(row);
+        }
+
+        Ok(Grid {
+            cells,
+            width,
+            height,
+            edge: Edge::Bounded,
+        })
+    }
+
+    pub fn from_fn<F>(width: usize, height: usize, f: F) -> Self
+    where
+        F: FnMut(Point2) -> T,
+    {
+        let cells = (0..height as i64)
+            .flat_map(|y| (0..width as i64).map(move |x| Point2::new(x, y)))
+            .map(f)
+            .collect();
+
+        Grid {
+            cells,
+            width,
+            height,
+            edge: Edge::Bounded,
+        }
+    }
+
+    /// Like [`Grid::from_fn`], computing the cells across the rayon thread
+    /// pool.
+    #[cfg(feature = "rayon")]
+    pub fn par_from_fn<F>(width: usize, height: usize, f: F) -> Self
+    where
+        T: Send,
+        F: Fn(Point2) -> T + Sync,
+    {
+        let cells = (0..width * height)
+            .into_par_iter()
+            .map(|i| f(Point2::new((i % width) as i64, (i / width) as i64)))
+            .collect();
+
+        Grid {
+            cells,
+            width,
+            height,
+            edge: Edge::Bounded,
+        }
+    }
+
+    pub fn with_edge(self, edge: Edge<T>) -> Self {
+        Grid { edge, ..self }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn edge(&self) -> &Edge<T> {
+        &self.edge
+    }
+
+    /// Whether `point` is one of the stored cells, regardless of the edge.
+    pub fn contains(&self, point: Point2) -> bool {
+        let (x, y) = (point.x(), point.y());
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+
+    fn offset(&self, point: Point2) -> Option<usize> {
+        let (x, y) = match self.edge {
+            // An empty grid has nothing to wrap around to.
+            Edge::Wrapping if self.cells.is_empty() => return None,
+            Edge::Wrapping => (
+                point.x().rem_euclid(self.width as i64),
+                point.y().rem_euclid(self.height as i64),
+            ),
+            _ if self.contains(point) => (point.x(), point.y()),
+            _ => return None,
+        };
+
+        Some(y as usize * self.width + x as usize)
+    }
+
+    pub fn get(&self, point: Point2) -> Option<&T> {
+        match (self.offset(point), &self.edge) {
+            (Some(offset), _) => Some(&self.cells[offset]),
+            (None, Edge::Infinite(background)) => Some(background),
+            (None, _) => None,
+        }
+    }
+
+    /// Mutable access to a stored cell; the background of an infinite grid
+    /// cannot be changed through this.
+    pub fn get_mut(&mut self, point: Point2) -> Option<&mut T> {
+        let offset = self.offset(point)?;
+        Some(&mut self.cells[offset])
+    }
+
+    /// Every stored point in row-major order.
+    pub fn points(&self) -> impl Iterator<Item = Point2> {
+        let width = self.width as i64;
+        (0..self.height as i64).flat_map(move |y| (0..width).map(move |x| Point2::new(x, y)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point2, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    /// The orthogonal neighbours of `point` that exist under this edge.
+    pub fn neighbors4(&self, point: Point2) -> impl Iterator<Item = (Point2, &T)> {
+        point
+            .orthogonal_neighbors()
+            .filter_map(move |neighbor| self.get(neighbor).map(|cell| (neighbor, cell)))
+    }
+
+    /// The orthogonal and diagonal neighbours of `point` that exist under
+    /// this edge.
+    pub fn neighbors8(&self, point: Point2) -> impl Iterator<Item = (Point2, &T)> {
+        point
+            .neighbors()
+            .filter_map(move |neighbor| self.get(neighbor).map(|cell| (neighbor, cell)))
+    }
+
+    /// Walks from `from`, exclusive, in steps of `direction`. Rays stop at
+    /// the edge of the stored cells unless the grid wraps, in which case
+    /// they never end. A zero `direction` goes nowhere and yields nothing.
+    pub fn ray(&self, from: Point2, direction: Point2) -> impl Iterator<Item = (Point2, &T)> {
+        let wrapping = matches!(self.edge, Edge::Wrapping) && !self.cells.is_empty();
+        let start = Some(from).filter(|_| direction != Point2::ORIGIN);
+        iter::successors(start, move |&point| Some(point + direction))
+            .skip(1)
+            .take_while(move |&point| wrapping || self.contains(point))
+            .filter_map(move |point| self.get(point).map(|cell| (point, cell)))
+    }
+}
+
+impl<T> Index<Point2> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point2) -> &Self::Output {
+        self.get(point)
+            .unwrap_or_else(|| panic!("{:?} is outside of the grid", point))
+    }
+}
+
+impl<T> IndexMut<Point2> for Grid<T> {
+    fn index_mut(&mut self, point: Point2) -> &mut Self::Output {
+        self.get_mut(point)
+            .unwrap_or_else(|| panic!("{:?} is outside of the grid", point))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // A grid with no columns has no cells to chunk.
+        for (i, row) in self.cells.chunks(self.width.max(1)).enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(input: &str) -> Grid<u32> {
+        Grid::parse(0, input, |ch| ch.to_digit(10).ok_or("expected a digit")).unwrap()
+    }
+
+    #[test]
+    fn parse_and_display_round_trip() {
+        let grid = digits("123\n456");
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[Point2::new(2, 1)], 6);
+        assert_eq!(grid.to_string(), "123\n456");
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = Grid::parse(0, "12\n3x", |ch| ch.to_digit(10).ok_or("expected a digit"));
+        assert_eq!(error.map_err(|e| (e.line, e.column)), Err((2, 2)));
+
+        let error = Grid::parse(0, "12\n345", |ch| ch.to_digit(10).ok_or("expected a digit"));
+        assert_eq!(error.map_err(|e| e.line), Err(2));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_from_fn_matches_from_fn() {
+        let f = |point: Point2| point.x() * 10 + point.y();
+        assert_eq!(Grid::par_from_fn(7, 5, f), Grid::from_fn(7, 5, f));
+    }
+
+    #[test]
+    fn edges() {
+        let bounded = digits("12\n34");
+        assert_eq!(bounded.get(Point2::new(2, 0)), None);
+
+        let wrapping = bounded.clone().with_edge(Edge::Wrapping);
+        assert_eq!(wrapping.get(Point2::new(2, 0)), Some(&1));
+        assert_eq!(wrapping.get(Point2::new(-1, -1)), Some(&4));
+
+        let infinite = bounded.with_edge(Edge::Infinite(0));
+        assert_eq!(infinite.get(Point2::new(5, -3)), Some(&0));
+        assert_eq!(infinite.get(Point2::new(1, 1)), Some(&4));
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = digits("123\n456\n789");
+        let corner = grid
+            .neighbors8(Point2::new(0, 0))
+            .map(|(_, &n)| n)
+            .collect::<Vec<_>>();
+        assert_eq!(corner, [2, 4, 5]);
+
+        let center = grid
+            .neighbors4(Point2::new(1, 1))
+            .map(|(_, &n)| n)
+            .collect::<Vec<_>>();
+        assert_eq!(center, [4, 6, 2, 8]);
+
+        let wrapping = grid.with_edge(Edge::Wrapping);
+        assert_eq!(wrapping.neighbors8(Point2::new(0, 0)).count(), 8);
+    }
+
+    #[test]
+    fn rays() {
+        let grid = digits("123\n456\n789");
+        let diagonal = grid
+            .ray(Point2::new(0, 0), Point2::new(1, 1))
+            .map(|(_, &n)| n)
+            .collect::<Vec<_>>();
+        assert_eq!(diagonal, [5, 9]);
+
+        let wrapping = grid.with_edge(Edge::Wrapping);
+        let row = wrapping
+            .ray(Point2::new(0, 0), Point2::new(1, 0))
+            .take(4)
+            .map(|(_, &n)| n);
+        assert_eq!(row.collect::<Vec<_>>(), [2, 3, 1, 2]);
+        assert_eq!(wrapping.ray(Point2::new(1, 1), Point2::ORIGIN).count(), 0);
+    }
+
+    #[test]
+    fn empty_grids() {
+        let grid = Grid::from_fn(0, 3, |_| 0).with_edge(Edge::Wrapping);
+        assert_eq!(grid.to_string(), "");
+        assert_eq!(grid.get(Point2::new(0, 0)), None);
+        assert_eq!(grid.ray(Point2::ORIGIN, Point2::new(1, 0)).count(), 0);
+    }
+}
@@ -1,23 +1,91 @@
 #[macro_use]
 extern crate aoc_runner_derive;
 
-mod day1;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
+// The shared modules are only built for the days that use them, and with no
+// days at all there is nothing to build.
+#[cfg(not(any(
+    feature = "day1",
+    feature = "day2",
+    feature = "day3",
+    feature = "day4",
+    feature = "day5",
+    feature = "day6",
+    feature = "day7",
+    feature = "day8",
+    feature = "day9",
+    feature = "day10",
+    feature = "day11",
+    feature = "day12",
+    feature = "day13",
+    feature = "day14",
+    feature = "day15",
+    feature = "day16",
+    feature = "day17",
+    feature = "day18"
+)))]
+compile_error!("enable at least one day feature, or `all` for every day");
 
+#[cfg(feature = "day1")]
+pub mod day1;
+#[cfg(feature = "day10")]
+pub mod day10;
+#[cfg(feature = "day11")]
+pub mod day11;
+#[cfg(feature = "day12")]
+pub mod day12;
+#[cfg(feature = "day13")]
+pub mod day13;
+#[cfg(feature = "day14")]
+pub mod day14;
+#[cfg(feature = "day15")]
+pub mod day15;
+#[cfg(feature = "day16")]
+pub mod day16;
+#[cfg(feature = "day17")]
+pub mod day17;
+#[cfg(feature = "day18")]
+pub mod day18;
+#[cfg(feature = "day2")]
+pub mod day2;
+#[cfg(feature = "day3")]
+pub mod day3;
+#[cfg(feature = "day4")]
+pub mod day4;
+#[cfg(feature = "day5")]
+pub mod day5;
+#[cfg(feature = "day6")]
+pub mod day6;
+#[cfg(feature = "day7")]
+pub mod day7;
+#[cfg(feature = "day8")]
+pub mod day8;
+#[cfg(feature = "day9")]
+pub mod day9;
+#[cfg(feature = "random")]
+pub mod differential;
+#[cfg(any(
+    feature = "day3",
+    feature = "day11",
+    feature = "day12",
+    feature = "day17"
+))]
+pub mod geometry;
+#[cfg(any(feature = "day11", feature = "day17"))]
+pub mod grid;
+pub mod memory;
+#[cfg(feature = "random")]
+pub mod oracle;
+mod parse;
+mod puzzle;
+#[cfg(feature = "random")]
+pub mod random;
+pub mod registry;
+pub mod report;
+pub mod trace;
+
+pub use parse::ParseError;
+pub use puzzle::Puzzle;
+
+// cargo-aoc only understands a single year per crate, so it keeps running
+// the 2020 days while the registry and runner cover every year.
 aoc_lib! { year = 2020}
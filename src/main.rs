@@ -0,0 +1,297 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+use advent_2020::{
+    memory::PeakAllocator,
+    registry::{self, Part, Solver},
+    report::{self, Format, Report},
+    trace::{self, Event},
+    ParseError,
+};
+use structopt::StructOpt;
+
+#[global_allocator]
+static ALLOCATOR: PeakAllocator = PeakAllocator;
+
+#[derive(StructOpt)]
+#[structopt(about = "Runs the Advent of Code solutions")]
+struct Options {
+    /// Day to run, every day is run when omitted
+    day: Option<u8>,
+    /// Year to run, the most recent year is run when omitted
+    #[structopt(short, long)]
+    year: Option<u16>,
+    /// Part to run, both parts are run when omitted
+    #[structopt(short, long, possible_values = &["1", "2"])]
+    part: Option<u8>,
+    /// Input file for the selected day, `-` reads from stdin
+    #[structopt(short, long, requires = "day", parse(from_os_str))]
+    input: Option<PathBuf>,
+    /// Directory of <year>/dayN.txt inputs used when no input file is given
+    #[structopt(long, default_value = "input", parse(from_os_str))]
+    input_dir: PathBuf,
+    /// Output format for answers and timings
+    #[structopt(short, long, default_value = "text", possible_values = &["text", "json", "csv"])]
+    format: Format,
+    /// Run days and parts concurrently across all cores, without peak memory
+    #[structopt(short = "j", long)]
+    parallel: bool,
+    /// Parse input while reading it, instead of reading it all first
+    #[structopt(long, conflicts_with = "parallel")]
+    stream: bool,
+    /// Write the steps each solver took to this file, `-` writes to stderr
+    #[structopt(long, conflicts_with_all = &["parallel", "stream"], parse(from_os_str))]
+    trace: Option<PathBuf>,
+    /// Format for the steps written by --trace
+    #[structopt(long, default_value = "text", possible_values = &["text", "json"])]
+    trace_format: Format,
+}
+
+/// Where the events captured while solving each part are written.
+struct Trace {
+    out: Box<dyn Write>,
+    format: Format,
+}
+
+impl Trace {
+    fn open(path: &Path, format: Format) -> io::Result<Self> {
+        let out: Box<dyn Write> = if path == Path::new("-") {
+            Box::new(io::stderr())
+        } else {
+            Box::new(io::BufWriter::new(File::create(path)?))
+        };
+        Ok(Trace { out, format })
+    }
+
+    fn write(&mut self, solver: &dyn Solver, part: Part, events: &[Event]) -> io::Result<()> {
+        for event in events {
+            match self.format {
+                Format::Json => writeln!(self.out, "{}", event.to_json(solver.year(), part))?,
+                _ => writeln!(
+                    self.out,
+                    "{} Day {:>2} - Part {}: {}",
+                    solver.year(),
+                    event.day,
+                    part,
+                    event
+                )?,
+            }
+        }
+        self.out.flush()
+    }
+}
+
+fn read_input(path: &Path) -> io::Result<String> {
+    if path == Path::new("-") {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        Ok(input)
+    } else {
+        fs::read_to_string(path)
+    }
+}
+
+fn open_input(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    if path == Path::new("-") {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        Ok(Box::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+fn input_path(solver: &dyn Solver, options: &Options) -> PathBuf {
+    options.input.clone().unwrap_or_else(|| {
+        options
+            .input_dir
+            .join(solver.year().to_string())
+            .join(format!("day{}.txt", solver.day()))
+    })
+}
+
+fn unreadable(solver: &dyn Solver, path: &Path, e: io::Error) {
+    eprintln!(
+        "{} day {}: unable to read {}: {}",
+        solver.year(),
+        solver.day(),
+        path.display(),
+        e
+    );
+}
+
+fn load(solver: &dyn Solver, options: &Options) -> Option<String> {
+    let path = input_path(solver, options);
+    match read_input(&path) {
+        Ok(input) => Some(input),
+        Err(e) => {
+            unreadable(solver, &path, e);
+            None
+        }
+    }
+}
+
+fn write<W: Write>(
+    solver: &dyn Solver,
+    records: Result<Vec<report::Record>, ParseError>,
+    report: &mut Report<W>,
+) -> bool {
+    let records = match records {
+        Ok(records) => records,
+        Err(e) => {
+            eprintln!("{} {}", solver.year(), e);
+            return false;
+        }
+    };
+
+    for record in &records {
+        if let Err(e) = report.write(record) {
+            eprintln!("unable to write report: {}", e);
+            return false;
+        }
+    }
+
+    true
+}
+
+fn run<W: Write>(
+    solver: &dyn Solver,
+    parts: &[Part],
+    options: &Options,
+    report: &mut Report<W>,
+    trace: Option<&mut Trace>,
+) -> bool {
+    if let Some(trace) = trace {
+        return run_traced(solver, parts, options, report, trace);
+    }
+
+    if !options.stream {
+        return match load(solver, options) {
+            Some(input) => write(solver, report::measure(solver, &input, parts), report),
+            None => false,
+        };
+    }
+
+    let path = input_path(solver, options);
+    match open_input(&path) {
+        Ok(mut reader) => {
+            let records = report::measure_reader(solver, &mut reader, parts);
+            write(solver, records, report)
+        }
+        Err(e) => {
+            unreadable(solver, &path, e);
+            false
+        }
+    }
+}
+
+/// Solves one part at a time, each with its own parse, so that every event
+/// can be tagged with the part it came from.
+fn run_traced<W: Write>(
+    solver: &dyn Solver,
+    parts: &[Part],
+    options: &Options,
+    report: &mut Report<W>,
+    trace: &mut Trace,
+) -> bool {
+    let input = match load(solver, options) {
+        Some(input) => input,
+        None => return false,
+    };
+
+    let mut success = true;
+    for &part in parts {
+        let (records, events) = trace::capture(|| report::measure(solver, &input, &[part]));
+        if let Err(e) = trace.write(solver, part, &events) {
+            eprintln!("unable to write trace: {}", e);
+            return false;
+        }
+        success &= write(solver, records, report);
+    }
+
+    success
+}
+
+/// Reads every input up front, solves them all concurrently and then writes
+/// the records in the same order as a sequential run would.
+fn run_parallel<W: Write>(
+    solvers: &[&dyn Solver],
+    parts: &[Part],
+    options: &Options,
+    report: &mut Report<W>,
+) -> bool {
+    let mut success = true;
+    let mut days = Vec::with_capacity(solvers.len());
+    for &solver in solvers {
+        match load(solver, options) {
+            Some(input) => days.push((solver, input)),
+            None => success = false,
+        }
+    }
+
+    for (&(solver, _), records) in days.iter().zip(report::measure_all(&days, parts)) {
+        success &= write(solver, records, report);
+    }
+
+    success
+}
+
+fn main() {
+    let options = Options::from_args();
+
+    let year = options.year.unwrap_or_else(registry::latest_year);
+    if registry::solvers(year).is_empty() {
+        eprintln!("year {} is not available", year);
+        process::exit(2);
+    }
+    let solvers = match options.day {
+        Some(day) => match registry::solver(year, day) {
+            Some(solver) => vec![solver],
+            None => {
+                eprintln!("{} day {} is not available", year, day);
+                process::exit(2);
+            }
+        },
+        None => registry::solvers(year).to_vec(),
+    };
+    let parts = match options.part.and_then(Part::from_number) {
+        Some(part) => vec![part],
+        None => vec![Part::One, Part::Two],
+    };
+
+    let stdout = io::stdout();
+    let mut report = match Report::new(stdout.lock(), options.format) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("unable to write report: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let mut trace = match &options.trace {
+        Some(path) => match Trace::open(path, options.trace_format) {
+            Ok(trace) => Some(trace),
+            Err(e) => {
+                eprintln!("unable to write trace to {}: {}", path.display(), e);
+                process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    let success = if options.parallel {
+        run_parallel(&solvers, &parts, &options, &mut report)
+    } else {
+        let mut success = true;
+        for solver in solvers {
+            success &= run(solver, &parts, &options, &mut report, trace.as_mut());
+        }
+        success
+    };
+
+    if !success {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,1016 @@
+//! Slow reference solutions that follow each puzzle statement as literally
+//! as possible, for checking the real solvers against.
+//!
+//! Nothing here shares code with the day modules: parsing is done from
+//! scratch on the raw text, searches are exhaustive and simulations run
+//! step by step. Where the statement leaves room for more than one answer
+//! (a tie, several matching sums, a loop that never settles) the reference
+//! gives no answer rather than guessing which one a solver should pick.
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use crate::registry::Part;
+
+/// The reference answer to one part of `input`, or `None` if there is no
+/// reference for the day or the input does not have exactly one
+/// well-defined answer.
+pub fn solve(year: u16, day: u8, input: &str, part: Part) -> Option<String> {
+    if year != 2020 {
+        return None;
+    }
+
+    let input = input.trim_end();
+    match day {
+        1 => day1(input, part),
+        2 => day2(input, part),
+        3 => day3(input, part),
+        4 => day4(input, part),
+        5 => day5(input, part),
+        6 => day6(input, part),
+        7 => day7(input, part),
+        8 => day8(input, part),
+        9 => day9(input, part),
+        10 => day10(input, part),
+        11 => day11(input, part),
+        12 => day12(input, part),
+        13 => day13(input, part),
+        14 => day14(input, part),
+        15 => day15(input, part),
+        16 => day16(input, part),
+        17 => day17(input, part),
+        18 => day18(input, part),
+        _ => None,
+    }
+}
+
+/// The single answer in `answers`, if there is exactly one.
+fn unique<T>(answers: impl IntoIterator<Item = T>) -> Option<String>
+where
+    T: ToString + Eq + Hash,
+{
+    let answers = answers.into_iter().collect::<HashSet<_>>();
+    if answers.len() == 1 {
+        answers.into_iter().next().map(|answer| answer.to_string())
+    } else {
+        None
+    }
+}
+
+fn numbers<T: std::str::FromStr>(input: &str) -> Option<Vec<T>> {
+    input.lines().map(|line| line.trim().parse().ok()).collect()
+}
+
+fn rows(input: &str) -> Option<Vec<Vec<char>>> {
+    let rows = input
+        .lines()
+        .map(|line| line.trim().chars().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let width = rows.first()?.len();
+    if width == 0 || rows.iter().any(|row| row.len() != width) {
+        return None;
+    }
+
+    Some(rows)
+}
+
+fn day1(input: &str, part: Part) -> Option<String> {
+    let entries = numbers::<u64>(input)?;
+    let n = entries.len();
+    let mut answers = Vec::new();
+    for i in 0..n {
+        for j in i + 1..n {
+            if part == Part::One {
+                if entries[i] + entries[j] == 2020 {
+                    answers.push(entries[i] * entries[j]);
+                }
+                continue;
+            }
+            for k in j + 1..n {
+                if entries[i] + entries[j] + entries[k] == 2020 {
+                    answers.push(entries[i] * entries[j] * entries[k]);
+                }
+            }
+        }
+    }
+
+    unique(answers)
+}
+
+fn day2(input: &str, part: Part) -> Option<String> {
+    let mut valid = 0;
+    for line in input.lines() {
+        let (policy, password) = line.split_once(": ")?;
+        let (range, letter) = policy.split_once(' ')?;
+        let (a, b) = range.split_once('-')?;
+        let (a, b) = (a.parse::<usize>().ok()?, b.parse::<usize>().ok()?);
+        let mut letters = letter.chars();
+        let letter = letters.next()?;
+        if letters.next().is_some() {
+            return None;
+        }
+
+        let ok = if part == Part::One {
+            let count = password.chars().filter(|&c| c == letter).count();
+            a <= count && count <= b
+        } else {
+            // Positions past either end hold nothing, so never the letter.
+            let at = |p: usize| p.checked_sub(1).and_then(|i| password.chars().nth(i));
+            let first = at(a) == Some(letter);
+            let second = at(b) == Some(letter);
+            first != second
+        };
+        if ok {
+            valid += 1;
+        }
+    }
+
+    Some(valid.to_string())
+}
+
+fn day3(input: &str, part: Part) -> Option<String> {
+    let rows = rows(input)?;
+    let slopes: &[(usize, usize)] = match part {
+        Part::One => &[(3, 1)],
+        Part::Two => &[(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)],
+    };
+
+    let mut product = 1u64;
+    for &(right, down) in slopes {
+        let mut trees = 0;
+        let (mut x, mut y) = (0, 0);
+        while y < rows.len() {
+            if rows[y][x % rows[y].len()] == '#' {
+                trees += 1;
+            }
+            x += right;
+            y += down;
+        }
+        product *= trees;
+    }
+
+    Some(product.to_string())
+}
+
+fn day4_year(value: &str, min: u32, max: u32) -> bool {
+    value.len() == 4
+        && value.chars().all(|c| c.is_ascii_digit())
+        && value
+            .parse::<u32>()
+            .is_ok_and(|year| min <= year && year <= max)
+}
+
+fn day4_valid(key: &str, value: &str) -> bool {
+    match key {
+        "byr" => day4_year(value, 1920, 2002),
+        "iyr" => day4_year(value, 2010, 2020),
+        "eyr" => day4_year(value, 2020, 2030),
+        "hgt" => {
+            let (number, min, max) = if let Some(number) = value.strip_suffix("cm") {
+                (number, 150, 193)
+            } else if let Some(number) = value.strip_suffix("in") {
+                (number, 59, 76)
+            } else {
+                return false;
+            };
+            !number.is_empty()
+                && number.chars().all(|c| c.is_ascii_digit())
+                && number.parse::<u32>().is_ok_and(|n| min <= n && n <= max)
+        }
+        "hcl" => {
+            value.len() == 7
+                && value.starts_with('#')
+                && value[1..]
+                    .chars()
+                    .all(|c| matches!(c, '0'..='9' | 'a'..='f'))
+        }
+        "ecl" => ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"].contains(&value),
+        "pid" => value.len() == 9 && value.chars().all(|c| c.is_ascii_digit()),
+        _ => true,
+    }
+}
+
+fn day4(input: &str, part: Part) -> Option<String> {
+    let required = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
+    let mut valid = 0;
+    for passport in input.split("\n\n") {
+        let mut fields = HashMap::new();
+        for field in passport.split_whitespace() {
+            let (key, value) = field.split_once(':')?;
+            fields.insert(key, value);
+        }
+
+        let ok = required.iter().all(|key| match fields.get(key) {
+            Some(value) => part == Part::One || day4_valid(key, value),
+            None => false,
+        });
+        if ok {
+            valid += 1;
+        }
+    }
+
+    Some(valid.to_string())
+}
+
+fn day5(input: &str, part: Part) -> Option<String> {
+    let mut ids = HashSet::new();
+    for line in input.lines() {
+        if line.len() != 10 {
+            return None;
+        }
+        let mut id = 0u32;
+        for c in line.chars() {
+            id = id * 2
+                + match c {
+                    'F' | 'L' => 0,
+                    'B' | 'R' => 1,
+                    _ => return None,
+                };
+        }
+        ids.insert(id);
+    }
+
+    match part {
+        Part::One => ids.iter().max().map(u32::to_string),
+        Part::Two => {
+            unique((1..1023).filter(|id| {
+                !ids.contains(id) && ids.contains(&(id - 1)) && ids.contains(&(id + 1))
+            }))
+        }
+    }
+}
+
+fn day6(input: &str, part: Part) -> Option<String> {
+    let mut total = 0;
+    for group in input.split("\n\n") {
+        let people = group.lines().collect::<Vec<_>>();
+        for question in 'a'..='z' {
+            let yes = people.iter().filter(|p| p.contains(question)).count();
+            let counts = match part {
+                Part::One => yes > 0,
+                Part::Two => yes == people.len(),
+            };
+            if counts {
+                total += 1;
+            }
+        }
+    }
+
+    Some(total.to_string())
+}
+
+type Day7Rules<'a> = HashMap<&'a str, Vec<(u64, String)>>;
+
+/// Whether `bag` eventually holds a shiny gold bag, or `None` on a cycle.
+fn day7_holds_gold<'a>(
+    rules: &'a Day7Rules<'a>,
+    bag: &'a str,
+    path: &mut Vec<&'a str>,
+) -> Option<bool> {
+    if path.contains(&bag) {
+        return None;
+    }
+    path.push(bag);
+    let mut holds = false;
+    for (_, inner) in rules.get(bag)? {
+        if inner == "shiny gold" || day7_holds_gold(rules, inner, path)? {
+            holds = true;
+        }
+    }
+    path.pop();
+
+    Some(holds)
+}
+
+/// How many bags `bag` holds in total, or `None` on a cycle.
+fn day7_count<'a>(rules: &'a Day7Rules<'a>, bag: &'a str, path: &mut Vec<&'a str>) -> Option<u64> {
+    if path.contains(&bag) {
+        return None;
+    }
+    path.push(bag);
+    let mut total = 0;
+    for (count, inner) in rules.get(bag)? {
+        total += count * (1 + day7_count(rules, inner, path)?);
+    }
+    path.pop();
+
+    Some(total)
+}
+
+fn day7(input: &str, part: Part) -> Option<String> {
+    let mut rules = HashMap::new();
+    for line in input.lines() {
+        let (outer, contents) = line.split_once(" bags contain ")?;
+        let mut inner = Vec::new();
+        if contents != "no other bags." {
+            for item in contents.trim_end_matches('.').split(", ") {
+                let words = item.split(' ').collect::<Vec<_>>();
+                match words[..] {
+                    [count, adjective, color, "bag"] | [count, adjective, color, "bags"] => {
+                        inner.push((count.parse().ok()?, format!("{} {}", adjective, color)))
+                    }
+                    _ => return None,
+                }
+            }
+        }
+        rules.insert(outer, inner);
+    }
+
+    match part {
+        Part::One => {
+            let mut count = 0;
+            for &bag in rules.keys() {
+                if bag != "shiny gold" && day7_holds_gold(&rules, bag, &mut Vec::new())? {
+                    count += 1;
+                }
+            }
+            Some(count.to_string())
+        }
+        Part::Two => day7_count(&rules, "shiny gold", &mut Vec::new()).map(|n| n.to_string()),
+    }
+}
+
+/// Runs a program until it repeats an instruction or steps just past the
+/// end, returning whether it finished and the accumulator at that point.
+fn day8_run(program: &[(&str, i64)]) -> Option<(bool, i64)> {
+    let mut visited = vec![false; program.len()];
+    let (mut ip, mut accumulator) = (0i64, 0);
+    loop {
+        if ip == program.len() as i64 {
+            return Some((true, accumulator));
+        }
+        if ip < 0 || ip > program.len() as i64 {
+            return None;
+        }
+        if visited[ip as usize] {
+            return Some((false, accumulator));
+        }
+        visited[ip as usize] = true;
+
+        match program[ip as usize] {
+            ("acc", n) => {
+                accumulator += n;
+                ip += 1;
+            }
+            ("jmp", n) => ip += n,
+            _ => ip += 1,
+        }
+    }
+}
+
+fn day8(input: &str, part: Part) -> Option<String> {
+    let mut program = Vec::new();
+    for line in input.lines() {
+        let (operation, argument) = line.split_once(' ')?;
+        if !["acc", "jmp", "nop"].contains(&operation) {
+            return None;
+        }
+        program.push((operation, argument.parse::<i64>().ok()?));
+    }
+
+    match part {
+        Part::One => match day8_run(&program)? {
+            (false, accumulator) => Some(accumulator.to_string()),
+            (true, _) => None,
+        },
+        Part::Two => {
+            let mut answers = Vec::new();
+            for i in 0..program.len() {
+                let mut repaired = program.clone();
+                repaired[i].0 = match program[i].0 {
+                    "jmp" => "nop",
+                    "nop" => "jmp",
+                    _ => continue,
+                };
+                if let Some((true, accumulator)) = day8_run(&repaired) {
+                    answers.push(accumulator);
+                }
+            }
+            unique(answers)
+        }
+    }
+}
+
+fn day9(input: &str, part: Part) -> Option<String> {
+    let numbers = numbers::<u64>(input)?;
+    let invalid = (25..numbers.len())
+        .find(|&i| {
+            let window = &numbers[i - 25..i];
+            !(0..25).any(|a| (a + 1..25).any(|b| window[a] + window[b] == numbers[i]))
+        })
+        .map(|i| numbers[i])?;
+
+    match part {
+        Part::One => Some(invalid.to_string()),
+        Part::Two => {
+            let mut answers = Vec::new();
+            for start in 0..numbers.len() {
+                for end in start + 2..=numbers.len() {
+                    let range = &numbers[start..end];
+                    if range.iter().sum::<u64>() == invalid {
+                        answers.push(range.iter().min()? + range.iter().max()?);
+                    }
+                }
+            }
+            unique(answers)
+        }
+    }
+}
+
+/// Counts every chain of adapters from `from` to the last one.
+fn day10_arrangements(adapters: &[u64], from: usize) -> u64 {
+    if from == adapters.len() - 1 {
+        return 1;
+    }
+
+    (from + 1..adapters.len())
+        .take_while(|&next| adapters[next] - adapters[from] <= 3)
+        .map(|next| day10_arrangements(adapters, next))
+        .sum()
+}
+
+fn day10(input: &str, part: Part) -> Option<String> {
+    let mut adapters = numbers::<u64>(input)?;
+    adapters.push(0);
+    adapters.sort_unstable();
+    let device = adapters.last()? + 3;
+    adapters.push(device);
+    let differences = adapters.windows(2).map(|w| w[1] - w[0]).collect::<Vec<_>>();
+    if differences.iter().any(|&d| d == 0 || d > 3) {
+        return None;
+    }
+
+    match part {
+        Part::One => {
+            let ones = differences.iter().filter(|&&d| d == 1).count();
+            let threes = differences.iter().filter(|&&d| d == 3).count();
+            Some((ones * threes).to_string())
+        }
+        Part::Two => Some(day10_arrangements(&adapters, 0).to_string()),
+    }
+}
+
+fn day11(input: &str, part: Part) -> Option<String> {
+    let mut seats = rows(input)?;
+    let (height, width) = (seats.len() as i64, seats[0].len() as i64);
+    let (tolerance, line_of_sight) = match part {
+        Part::One => (4, false),
+        Part::Two => (5, true),
+    };
+
+    let mut seen = HashSet::new();
+    loop {
+        if !seen.insert(seats.clone()) {
+            return None;
+        }
+
+        let mut next = seats.clone();
+        for y in 0..height {
+            for x in 0..width {
+                let mut occupied = 0;
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        if (dx, dy) == (0, 0) {
+                            continue;
+                        }
+                        let (mut nx, mut ny) = (x + dx, y + dy);
+                        while 0 <= nx && nx < width && 0 <= ny && ny < height {
+                            let seat = seats[ny as usize][nx as usize];
+                            if seat == '#' {
+                                occupied += 1;
+                            }
+                            if seat != '.' || !line_of_sight {
+                                break;
+                            }
+                            nx += dx;
+                            ny += dy;
+                        }
+                    }
+                }
+
+                let seat = &mut next[y as usize][x as usize];
+                if *seat == 'L' && occupied == 0 {
+                    *seat = '#';
+                } else if *seat == '#' && occupied >= tolerance {
+                    *seat = 'L';
+                }
+            }
+        }
+
+        if next == seats {
+            let occupied = seats.iter().flatten().filter(|&&c| c == '#').count();
+            return Some(occupied.to_string());
+        }
+        seats = next;
+    }
+}
+
+fn day12(input: &str, part: Part) -> Option<String> {
+    let (mut x, mut y) = (0i64, 0i64);
+    let mut heading = 0;
+    let (mut wx, mut wy) = (10i64, 1i64);
+    for line in input.lines() {
+        let action = line.chars().next()?;
+        let value = line[action.len_utf8()..].parse::<i64>().ok()?;
+        if !"NSEWLRF".contains(action) || matches!(action, 'L' | 'R') && value % 90 != 0 {
+            return None;
+        }
+
+        let (dx, dy) = match action {
+            'N' => (0, value),
+            'S' => (0, -value),
+            'E' => (value, 0),
+            'W' => (-value, 0),
+            _ => (0, 0),
+        };
+        let turns = match action {
+            'L' => value / 90,
+            'R' => -value / 90,
+            _ => 0,
+        };
+
+        if part == Part::One {
+            x += dx;
+            y += dy;
+            heading = (heading + 90 * turns).rem_euclid(360);
+            if action == 'F' {
+                match heading {
+                    0 => x += value,
+                    90 => y += value,
+                    180 => x -= value,
+                    _ => y -= value,
+                }
+            }
+        } else {
+            wx += dx;
+            wy += dy;
+            for _ in 0..turns.rem_euclid(4) {
+                let (rx, ry) = (-wy, wx);
+                wx = rx;
+                wy = ry;
+            }
+            if action == 'F' {
+                x += wx * value;
+                y += wy * value;
+            }
+        }
+    }
+
+    Some((x.abs() + y.abs()).to_string())
+}
+
+fn day13(input: &str, part: Part) -> Option<String> {
+    let mut lines = input.lines();
+    let start = lines.next()?.trim().parse::<u64>().ok()?;
+    let mut busses = Vec::new();
+    for (offset, bus) in lines.next()?.split(',').enumerate() {
+        if bus != "x" {
+            busses.push((offset as u64, bus.parse::<u64>().ok()?));
+        }
+    }
+    if busses.iter().any(|&(_, bus)| bus == 0) {
+        return None;
+    }
+
+    match part {
+        Part::One => {
+            let wait = |bus: u64| (bus - start % bus) % bus;
+            let soonest = busses.iter().map(|&(_, bus)| wait(bus)).min()?;
+            unique(
+                busses
+                    .iter()
+                    .filter(|&&(_, bus)| wait(bus) == soonest)
+                    .map(|&(_, bus)| bus * soonest),
+            )
+        }
+        Part::Two => {
+            // Sieve one bus at a time, stepping by the product of the busses
+            // already lined up, which needs their IDs to share no factors.
+            let gcd = |mut a: u64, mut b: u64| {
+                while b != 0 {
+                    let r = a % b;
+                    a = b;
+                    b = r;
+                }
+                a
+            };
+            for (i, &(_, a)) in busses.iter().enumerate() {
+                if busses[i + 1..].iter().any(|&(_, b)| gcd(a, b) != 1) {
+                    return None;
+                }
+            }
+
+            let (mut time, mut step) = (0, 1);
+            for &(offset, bus) in &busses {
+                while (time + offset) % bus != 0 {
+                    time += step;
+                }
+                step *= bus;
+            }
+            Some(time.to_string())
+        }
+    }
+}
+
+/// Every address `address` expands to under a version 2 mask.
+fn day14_addresses(mask: &[char], address: u64) -> Vec<u64> {
+    let mut addresses = vec![0];
+    for (i, &bit) in mask.iter().enumerate() {
+        let value = 1 << (35 - i);
+        let options: &[u64] = match bit {
+            '1' => &[1],
+            'X' => &[0, 1],
+            _ if address & value != 0 => &[1],
+            _ => &[0],
+        };
+        addresses = addresses
+            .iter()
+            .flat_map(|&a| options.iter().map(move |&o| a | (o * value)))
+            .collect();
+    }
+
+    addresses
+}
+
+fn day14(input: &str, part: Part) -> Option<String> {
+    let mut mask = Vec::new();
+    let mut memory = HashMap::new();
+    for line in input.lines() {
+        let (target, value) = line.split_once(" = ")?;
+        if target == "mask" {
+            mask = value.chars().collect::<Vec<_>>();
+            if mask.len() != 36 || mask.iter().any(|c| !"X01".contains(*c)) {
+                return None;
+            }
+            continue;
+        }
+
+        let address = target.strip_prefix("mem[")?.strip_suffix(']')?;
+        let (address, value) = (address.parse::<u64>().ok()?, value.parse::<u64>().ok()?);
+        if mask.is_empty() || value >= 1 << 36 || address >= 1 << 36 {
+            return None;
+        }
+
+        if part == Part::One {
+            let mut masked = 0;
+            for (i, &bit) in mask.iter().enumerate() {
+                let place = 1 << (35 - i);
+                let set = match bit {
+                    '1' => true,
+                    '0' => false,
+                    _ => value & place != 0,
+                };
+                if set {
+                    masked |= place;
+                }
+            }
+            memory.insert(address, masked);
+        } else {
+            for address in day14_addresses(&mask, address) {
+                memory.insert(address, value);
+            }
+        }
+    }
+
+    Some(memory.values().sum::<u64>().to_string())
+}
+
+fn day15(input: &str, part: Part) -> Option<String> {
+    let mut spoken = input
+        .trim()
+        .split(',')
+        .map(|n| n.trim().parse::<usize>().ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    match part {
+        Part::One => {
+            while spoken.len() < 2020 {
+                let (&last, earlier) = spoken.split_last()?;
+                let next = earlier
+                    .iter()
+                    .rposition(|&n| n == last)
+                    .map_or(0, |i| earlier.len() - i);
+                spoken.push(next);
+            }
+            spoken.last().map(usize::to_string)
+        }
+        Part::Two => {
+            // Scanning back through thirty million turns is out of reach, so
+            // remember the turn each number was last spoken on instead.
+            const TURNS: usize = 30_000_000;
+            let mut last_turn = vec![0u32; TURNS.max(spoken.len())];
+            let (&first, earlier) = spoken.split_last()?;
+            for (turn, &n) in earlier.iter().enumerate() {
+                *last_turn.get_mut(n)? = turn as u32 + 1;
+            }
+            let mut current = first;
+            for turn in spoken.len()..TURNS {
+                let previous = *last_turn.get(current)?;
+                last_turn[current] = turn as u32;
+                current = if previous == 0 {
+                    0
+                } else {
+                    turn - previous as usize
+                };
+            }
+            Some(current.to_string())
+        }
+    }
+}
+
+/// Counts the ways to give each field its own rule, stopping at two.
+fn day16_assignments(
+    candidates: &[(usize, Vec<usize>)],
+    used: &mut Vec<bool>,
+    chosen: &mut Vec<(usize, usize)>,
+    found: &mut Vec<Vec<(usize, usize)>>,
+) {
+    if found.len() > 1 {
+        return;
+    }
+    let (field, rules) = match candidates.split_first() {
+        Some((first, _)) => first,
+        None => {
+            found.push(chosen.clone());
+            return;
+        }
+    };
+
+    for &rule in rules {
+        if !used[rule] {
+            used[rule] = true;
+            chosen.push((*field, rule));
+            day16_assignments(&candidates[1..], used, chosen, found);
+            chosen.pop();
+            used[rule] = false;
+        }
+    }
+}
+
+fn day16(input: &str, part: Part) -> Option<String> {
+    let sections = input.split("\n\n").collect::<Vec<_>>();
+    if sections.len() != 3 {
+        return None;
+    }
+
+    let mut rules = Vec::new();
+    for line in sections[0].lines() {
+        let (name, ranges) = line.split_once(": ")?;
+        let mut bounds = Vec::new();
+        for range in ranges.split(" or ") {
+            let (low, high) = range.split_once('-')?;
+            bounds.push((low.parse::<u64>().ok()?, high.parse::<u64>().ok()?));
+        }
+        rules.push((name, bounds));
+    }
+    let ticket =
+        |line: &str| -> Option<Vec<u64>> { line.split(',').map(|n| n.parse().ok()).collect() };
+    let mine = ticket(sections[1].lines().nth(1)?)?;
+    let nearby = sections[2]
+        .lines()
+        .skip(1)
+        .map(ticket)
+        .collect::<Option<Vec<_>>>()?;
+
+    let matches = |rule: usize, value: u64| {
+        rules[rule]
+            .1
+            .iter()
+            .any(|&(low, high)| low <= value && value <= high)
+    };
+    let any_rule = |value: u64| (0..rules.len()).any(|rule| matches(rule, value));
+
+    if part == Part::One {
+        let error_rate = nearby
+            .iter()
+            .flatten()
+            .filter(|&&value| !any_rule(value))
+            .sum::<u64>();
+        return Some(error_rate.to_string());
+    }
+
+    let valid = nearby
+        .iter()
+        .filter(|ticket| ticket.iter().all(|&value| any_rule(value)))
+        .collect::<Vec<_>>();
+    if valid.iter().any(|ticket| ticket.len() != mine.len()) || mine.len() != rules.len() {
+        return None;
+    }
+
+    // Trying the fields with the fewest candidates first only prunes the
+    // search; every assignment is still visited.
+    let mut candidates = (0..mine.len())
+        .map(|field| {
+            let fits = (0..rules.len())
+                .filter(|&rule| valid.iter().all(|ticket| matches(rule, ticket[field])))
+                .collect::<Vec<_>>();
+            (field, fits)
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|(_, fits)| fits.len());
+
+    let mut found = Vec::new();
+    day16_assignments(
+        &candidates,
+        &mut vec![false; rules.len()],
+        &mut Vec::new(),
+        &mut found,
+    );
+    if found.len() != 1 {
+        return None;
+    }
+
+    let product = found[0]
+        .iter()
+        .filter(|&&(_, rule)| rules[rule].0.starts_with("departure"))
+        .map(|&(field, _)| mine[field])
+        .product::<u64>();
+    Some(product.to_string())
+}
+
+fn day17(input: &str, part: Part) -> Option<String> {
+    let rows = rows(input)?;
+    let mut active = HashSet::new();
+    for (y, row) in rows.iter().enumerate() {
+        for (x, &cell) in row.iter().enumerate() {
+            match cell {
+                '#' => {
+                    active.insert([x as i64, y as i64, 0, 0]);
+                }
+                '.' => {}
+                _ => return None,
+            }
+        }
+    }
+    let w_range = match part {
+        Part::One => 0..=0,
+        Part::Two => -1..=1,
+    };
+
+    for _ in 0..6 {
+        let mut candidates = HashSet::new();
+        for cube in &active {
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        for dw in w_range.clone() {
+                            candidates.insert([
+                                cube[0] + dx,
+                                cube[1] + dy,
+                                cube[2] + dz,
+                                cube[3] + dw,
+                            ]);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut next = HashSet::new();
+        for cube in candidates {
+            let mut neighbors = 0;
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        for dw in w_range.clone() {
+                            let other = [cube[0] + dx, cube[1] + dy, cube[2] + dz, cube[3] + dw];
+                            if other != cube && active.contains(&other) {
+                                neighbors += 1;
+                            }
+                        }
+                    }
+                }
+            }
+
+            let is_active = active.contains(&cube);
+            if neighbors == 3 || (is_active && neighbors == 2) {
+                next.insert(cube);
+            }
+        }
+        active = next;
+    }
+
+    Some(active.len().to_string())
+}
+
+/// A recursive descent evaluator over the tokens of one expression, where
+/// `+` binds tighter than `*` if `addition_first`, or neither does.
+struct Day18Parser {
+    tokens: Vec<char>,
+    position: usize,
+    addition_first: bool,
+}
+
+impl Day18Parser {
+    fn peek(&self) -> Option<char> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn term(&mut self) -> Option<i64> {
+        match self.peek()? {
+            '(' => {
+                self.position += 1;
+                let value = self.expression()?;
+                if self.peek()? != ')' {
+                    return None;
+                }
+                self.position += 1;
+                Some(value)
+            }
+            _ => {
+                let start = self.position;
+                while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    self.position += 1;
+                }
+                if start == self.position {
+                    return None;
+                }
+                let digits = self.tokens[start..self.position].iter().collect::<String>();
+                digits.parse().ok()
+            }
+        }
+    }
+
+    fn sum(&mut self) -> Option<i64> {
+        let mut value = self.term()?;
+        while self.peek() == Some('+') {
+            self.position += 1;
+            value = value.checked_add(self.term()?)?;
+        }
+        Some(value)
+    }
+
+    fn expression(&mut self) -> Option<i64> {
+        if self.addition_first {
+            let mut value = self.sum()?;
+            while self.peek() == Some('*') {
+                self.position += 1;
+                value = value.checked_mul(self.sum()?)?;
+            }
+            return Some(value);
+        }
+
+        let mut value = self.term()?;
+        while let Some(operator) = self.peek().filter(|&c| c == '+' || c == '*') {
+            self.position += 1;
+            let rhs = self.term()?;
+            value = match operator {
+                '+' => value.checked_add(rhs)?,
+                _ => value.checked_mul(rhs)?,
+            };
+        }
+        Some(value)
+    }
+}
+
+fn day18(input: &str, part: Part) -> Option<String> {
+    let mut total = 0i64;
+    for line in input.lines() {
+        let mut parser = Day18Parser {
+            tokens: line.chars().filter(|c| !c.is_whitespace()).collect(),
+            position: 0,
+            addition_first: part == Part::Two,
+        };
+        let value = parser.expression()?;
+        if parser.position != parser.tokens.len() {
+            return None;
+        }
+        total = total.checked_add(value)?;
+    }
+
+    Some(total.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn examples() {
+        let day1 = "1721\n979\n366\n299\n675\n1456";
+        assert_eq!(solve(2020, 1, day1, Part::One), Some("514579".to_string()));
+        assert_eq!(
+            solve(2020, 1, day1, Part::Two),
+            Some("241861950".to_string())
+        );
+
+        let day13 = "939\n7,13,x,x,59,x,31,19";
+        assert_eq!(solve(2020, 13, day13, Part::One), Some("295".to_string()));
+        assert_eq!(
+            solve(2020, 13, day13, Part::Two),
+            Some("1068781".to_string())
+        );
+
+        let day18 = "2 * 3 + (4 * 5)\n((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2";
+        assert_eq!(solve(2020, 18, day18, Part::One), Some("13658".to_string()));
+        assert_eq!(solve(2020, 18, day18, Part::Two), Some("23386".to_string()));
+    }
+
+    #[test]
+    fn ambiguous_inputs_have_no_answer() {
+        assert_eq!(solve(2020, 1, "1000\n1020\n1010\n1010", Part::One), None);
+        assert_eq!(solve(2020, 8, "nop +0\nacc +1", Part::One), None);
+        assert_eq!(solve(2020, 13, "11\n3,4", Part::One), None);
+    }
+}
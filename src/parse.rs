@@ -0,0 +1,288 @@
+use std::{error::Error, fmt};
+
+/// A malformed piece of puzzle input, located by day, line and column.
+///
+/// Lines and columns are 1-based and columns count characters, not bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u8,
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(day: u8, text: impl Into<String>, message: impl fmt::Display) -> Self {
+        ParseError {
+            day,
+            line: 1,
+            column: 1,
+            text: text.into(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Builds an error for `span`, which must be a slice of `source`, with
+    /// the column pointing at the start of the span.
+    pub fn at(day: u8, source: &str, span: &str, message: impl fmt::Display) -> Self {
+        Self::new(day, span, message).with_column(column(source, span))
+    }
+
+    pub fn with_line(self, line: usize) -> Self {
+        ParseError { line, ..self }
+    }
+
+    pub fn with_column(self, column: usize) -> Self {
+        ParseError { column, ..self }
+    }
+
+    /// Shifts the line number down by `lines`, for errors raised while
+    /// parsing a section that does not start at the top of the input.
+    pub fn offset_lines(self, lines: usize) -> Self {
+        ParseError {
+            line: self.line + lines,
+            ..self
+        }
+    }
+
+    /// Shifts the column to account for `span` starting part way into
+    /// `source`, for errors raised while parsing a piece of a line.
+    pub fn relative_to(self, source: &str, span: &str) -> Self {
+        ParseError {
+            column: self.column + column(source, span) - 1,
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "day {}, line {}, column {}: {} ({:?})",
+            self.day, self.line, self.column, self.message, self.text
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+fn column(source: &str, span: &str) -> usize {
+    let start = source.as_ptr() as usize;
+    let offset = (span.as_ptr() as usize).wrapping_sub(start);
+    source
+        .get(..offset)
+        .map(|prefix| prefix.chars().count() + 1)
+        .unwrap_or(1)
+}
+
+/// Parses `span`, a slice of `source`, reporting failures at its position.
+#[cfg(any(
+    feature = "day1",
+    feature = "day2",
+    feature = "day7",
+    feature = "day8",
+    feature = "day9",
+    feature = "day10",
+    feature = "day12",
+    feature = "day13",
+    feature = "day14",
+    feature = "day15",
+    feature = "day16"
+))]
+pub(crate) fn field<T>(day: u8, source: &str, span: &str) -> Result<T, ParseError>
+where
+    T: std::str::FromStr,
+    T::Err: fmt::Display,
+{
+    span.parse()
+        .map_err(|e| ParseError::at(day, source, span, e))
+}
+
+/// Parses every line of `input` with `f`, tagging errors with their line.
+#[cfg(any(
+    feature = "day1",
+    feature = "day2",
+    feature = "day3",
+    feature = "day4",
+    feature = "day5",
+    feature = "day6",
+    feature = "day7",
+    feature = "day8",
+    feature = "day9",
+    feature = "day10",
+    feature = "day11",
+    feature = "day12",
+    feature = "day14",
+    feature = "day16",
+    feature = "day17",
+    feature = "day18"
+))]
+pub(crate) fn lines<T, F>(input: &str, mut f: F) -> Result<Vec<T>, ParseError>
+where
+    F: FnMut(&str) -> Result<T, ParseError>,
+{
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| f(line).map_err(|e| e.with_line(i + 1)))
+        .collect()
+}
+
+/// Rewrites CRLF line endings as LF, trims stray whitespace from each line
+/// and drops trailing blank lines, so that input saved by any editor parses
+/// the same as the canonical puzzle input. Line numbers are preserved.
+pub(crate) fn normalize(input: &str) -> String {
+    let mut lines = input.lines().map(str::trim).collect::<Vec<_>>();
+    while lines.last() == Some(&"") {
+        lines.pop();
+    }
+
+    lines.join("\n")
+}
+
+/// Reads `reader` one line at a time, parsing each with `f` the same way as
+/// [`lines`] would parse the [`normalize`]d input, without ever holding more
+/// than one line of text.
+#[cfg(any(
+    feature = "day1",
+    feature = "day3",
+    feature = "day8",
+    feature = "day9",
+    feature = "day10",
+    feature = "day12",
+    feature = "day14"
+))]
+pub(crate) fn read_lines<R, T, F>(day: u8, mut reader: R, mut f: F) -> Result<Vec<T>, ParseError>
+where
+    R: std::io::BufRead,
+    F: FnMut(&str) -> Result<T, ParseError>,
+{
+    let mut values = Vec::new();
+    let mut buffer = String::new();
+    let mut number = 0;
+    // Blank lines only matter if something follows them, so hold them back
+    // until the next non-blank line turns up.
+    let mut blank = None;
+    loop {
+        buffer.clear();
+        let read = reader.read_line(&mut buffer).map_err(|e| {
+            ParseError::new(day, "", format!("unable to read input: {}", e)).with_line(number + 1)
+        })?;
+        if read == 0 {
+            return Ok(values);
+        }
+        number += 1;
+
+        let line = buffer.trim();
+        if line.is_empty() {
+            blank = blank.or(Some(number));
+            continue;
+        }
+        if let Some(first) = blank.take() {
+            for line in first..number {
+                values.push(f("").map_err(|e| e.with_line(line))?);
+            }
+        }
+        values.push(f(line).map_err(|e| e.with_line(number))?);
+    }
+}
+
+/// Splits `input` on blank lines, yielding each paragraph along with the
+/// number of lines that precede it. Runs of blank lines count as one break.
+#[cfg(any(feature = "day4", feature = "day6", feature = "day16"))]
+pub(crate) fn paragraphs(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut paragraphs = Vec::new();
+    let mut start = None;
+    let mut offset = 0;
+    for (i, line) in input.split('\n').enumerate() {
+        if line.trim().is_empty() {
+            if let Some((first_line, from)) = start.take() {
+                paragraphs.push((first_line, &input[from..offset - 1]));
+            }
+        } else if start.is_none() {
+            start = Some((i, offset));
+        }
+        offset += line.len() + 1;
+    }
+    if let Some((first_line, from)) = start {
+        paragraphs.push((first_line, &input[from..]));
+    }
+
+    paragraphs.into_iter()
+}
+
+/// Reports the character starting at byte offset `index` of `line`.
+#[cfg(any(
+    feature = "day2",
+    feature = "day3",
+    feature = "day5",
+    feature = "day6",
+    feature = "day11",
+    feature = "day14",
+    feature = "day17"
+))]
+pub(crate) fn unexpected(day: u8, line: &str, index: usize, message: &str) -> ParseError {
+    let end = line[index..]
+        .chars()
+        .next()
+        .map_or(index, |ch| index + ch.len_utf8());
+    ParseError::at(day, line, &line[index..end], message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_line_endings_and_whitespace() {
+        assert_eq!(normalize("a \r\n  b\r\n\r\nc\t\r\n\r\n \n"), "a\nb\n\nc");
+    }
+
+    #[cfg(any(
+        feature = "day1",
+        feature = "day3",
+        feature = "day8",
+        feature = "day9",
+        feature = "day10",
+        feature = "day12",
+        feature = "day14"
+    ))]
+    #[test]
+    fn read_lines_matches_lines() {
+        let parse = |line: &str| -> Result<String, ParseError> {
+            match line {
+                "" => Err(ParseError::new(0, line, "blank")),
+                _ => Ok(line.to_string()),
+            }
+        };
+        let read = |input: &str| read_lines(0, input.as_bytes(), parse);
+
+        for input in &["", "a \r\n  b\r\nc\t\r\n\r\n \n", "a\n\n\nb", "\nc"] {
+            assert_eq!(read(input), lines(&normalize(input), parse), "{:?}", input);
+        }
+        assert_eq!(read("a\n\n\nb").map_err(|e| e.line), Err(2));
+
+        let invalid = read_lines(0, &[b'1', b'\n', 0xff][..], parse);
+        assert_eq!(invalid.map_err(|e| e.line), Err(2));
+    }
+
+    #[cfg(any(feature = "day4", feature = "day6", feature = "day16"))]
+    #[test]
+    fn paragraphs_track_line_offsets() {
+        let input = "a\nb\n\n\n\nc\n\nd\ne\n";
+        assert_eq!(
+            paragraphs(input).collect::<Vec<_>>(),
+            [(0, "a\nb"), (5, "c"), (7, "d\ne")]
+        );
+    }
+
+    #[test]
+    fn error_columns_count_characters() {
+        let line = "çà va";
+        let error = ParseError::at(1, line, &line[5..], "message");
+        assert_eq!(error.column, 4);
+        assert_eq!(error.text, "va");
+    }
+}
@@ -0,0 +1,28 @@
+use std::{fmt::Display, io::BufRead};
+
+use crate::ParseError;
+
+/// A single day's puzzle: how to parse its input and how to solve each part.
+pub trait Puzzle {
+    const YEAR: u16;
+    const DAY: u8;
+
+    type Input;
+    type Part1: Display;
+    type Part2: Display;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+    fn part1(input: &Self::Input) -> Self::Part1;
+    fn part2(input: &Self::Input) -> Self::Part2;
+
+    /// Parses input as it is read from `reader`. Days whose input is a list
+    /// of independent lines override this to avoid reading it all up front.
+    fn parse_reader<R: BufRead>(mut reader: R) -> Result<Self::Input, ParseError> {
+        let mut input = String::new();
+        reader
+            .read_to_string(&mut input)
+            .map_err(|e| ParseError::new(Self::DAY, "", format!("unable to read input: {}", e)))?;
+
+        Self::parse(&input)
+    }
+}
@@ -0,0 +1,779 @@
+//! Random puzzle inputs for property and load testing.
+//!
+//! Every input is accepted by its day's generator and, beyond that, is built
+//! so that both parts have an answer and finish without overflowing: sums
+//! that the puzzle promises exist are planted, graphs are acyclic and
+//! growth is capped. The same day, size and seed always give the same text.
+//!
+//! Only the 2020 days have generators so far. What `size` counts depends on
+//! the day:
+//!
+//! | Day | Size | Day | Size |
+//! |-----|------|-----|------|
+//! | 1 | entries, at least 5 | 10 | adapters |
+//! | 2 | password lines | 11 | rows and columns |
+//! | 3 | rows of a 31 wide map | 12 | navigation instructions |
+//! | 4 | passports | 13 | buses, as many as fit |
+//! | 5 | boarding passes, 2 to 1000 | 14 | masks |
+//! | 6 | groups | 15 | starting numbers, up to 1000 |
+//! | 7 | bag colours, up to 594 | 16 | nearby tickets, at least 25 |
+//! | 8 | instructions, up to 30000 | 17 | rows and columns |
+//! | 9 | numbers, 27 to 1000 | 18 | expressions |
+use std::collections::HashSet;
+
+use itertools::Itertools;
+use rand::{
+    seq::{index, SliceRandom},
+    Rng, SeedableRng,
+};
+use rand_chacha::ChaCha8Rng;
+
+/// Generates input for `day` of `year` with the given size and seed, or
+/// `None` if there is no generator for that day.
+pub fn input(year: u16, day: u8, size: usize, seed: u64) -> Option<String> {
+    if year != 2020 {
+        return None;
+    }
+
+    let rng = &mut ChaCha8Rng::seed_from_u64(seed);
+    let input = match day {
+        1 => day1(rng, size),
+        2 => day2(rng, size),
+        3 => grid(rng, 31, size, &[('.', 4), ('#', 1)]),
+        4 => day4(rng, size),
+        5 => day5(rng, size),
+        6 => day6(rng, size),
+        7 => day7(rng, size),
+        8 => day8(rng, size),
+        9 => day9(rng, size),
+        10 => day10(rng, size),
+        11 => day11(rng, size),
+        12 => day12(rng, size),
+        13 => day13(rng, size),
+        14 => day14(rng, size),
+        15 => day15(rng, size),
+        16 => day16(rng, size),
+        17 => grid(rng, size, size, &[('.', 1), ('#', 1)]),
+        18 => day18(rng, size),
+        _ => return None,
+    };
+
+    Some(input)
+}
+
+fn lines<I>(lines: I) -> String
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    lines
+        .into_iter()
+        .map(|line| line.as_ref().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn lowercase(rng: &mut impl Rng) -> char {
+    rng.gen_range(b'a'..=b'z') as char
+}
+
+/// A `width` by `height` map of cells picked with the given weights.
+fn cells(rng: &mut impl Rng, width: usize, height: usize, cells: &[(char, u32)]) -> Vec<Vec<char>> {
+    let (width, height) = (width.max(1), height.max(1));
+    (0..height)
+        .map(|_| {
+            (0..width)
+                .map(|_| cells.choose_weighted(rng, |&(_, w)| w).unwrap().0)
+                .collect()
+        })
+        .collect()
+}
+
+fn grid(rng: &mut impl Rng, width: usize, height: usize, weights: &[(char, u32)]) -> String {
+    let rows = cells(rng, width, height, weights);
+    lines(rows.iter().map(|row| row.iter().collect::<String>()))
+}
+
+/// Whether `x` would give a second pair or triple summing to 2020.
+fn day1_completes_sum(entries: &HashSet<u32>, x: u32) -> bool {
+    if x >= 2020 {
+        return false;
+    }
+
+    let rest = 2020 - x;
+    entries.contains(&rest)
+        || entries
+            .iter()
+            .any(|&y| y < rest && y * 2 != rest && entries.contains(&(rest - y)))
+}
+
+fn day1(rng: &mut impl Rng, size: usize) -> String {
+    // Plant one pair and one triple, retrying until they are the only ones.
+    let mut entries = HashSet::new();
+    while entries.is_empty() {
+        let a = rng.gen_range(1..1010);
+        let c = rng.gen_range(1..673);
+        let d = rng.gen_range(c + 1..(2021 - c) / 2);
+        let planted = [a, 2020 - a, c, d, 2020 - c - d];
+        let distinct = planted.iter().collect::<HashSet<_>>().len() == planted.len();
+        let pairs = planted
+            .iter()
+            .tuple_combinations()
+            .filter(|&(x, y)| x + y == 2020)
+            .count();
+        let triples = planted
+            .iter()
+            .tuple_combinations()
+            .filter(|&(x, y, z)| x + y + z == 2020)
+            .count();
+        if distinct && pairs == 1 && triples == 1 {
+            entries.extend(planted.iter());
+        }
+    }
+
+    while entries.len() < size {
+        let x = (0..20)
+            .map(|_| rng.gen_range(1..2020))
+            .find(|&x| !entries.contains(&x) && !day1_completes_sum(&entries, x))
+            .unwrap_or_else(|| rng.gen_range(2020..1_000_000));
+        entries.insert(x);
+    }
+
+    let mut entries = entries.into_iter().collect::<Vec<_>>();
+    entries.sort_unstable();
+    entries.shuffle(rng);
+    lines(entries.iter().map(u32::to_string))
+}
+
+fn day2(rng: &mut impl Rng, size: usize) -> String {
+    let entries = (0..size).map(|_| {
+        let letter = lowercase(rng);
+        let length = rng.gen_range(1..=16);
+        let password = (0..length)
+            .map(|_| {
+                if rng.gen_bool(0.3) {
+                    letter
+                } else if rng.gen_bool(0.1) {
+                    *['é', 'ß', '#', '!'].choose(rng).unwrap()
+                } else {
+                    lowercase(rng)
+                }
+            })
+            .collect::<String>();
+        // Positions now and then fall outside the password.
+        let first = rng.gen_range(0..=length);
+        let last = rng.gen_range(first..=length + 2);
+        format!("{}-{} {}: {}", first, last, letter, password)
+    });
+
+    lines(entries)
+}
+
+fn day4_year(rng: &mut impl Rng, valid: bool, min: u16, max: u16) -> u16 {
+    if valid {
+        rng.gen_range(min..=max)
+    } else if rng.gen_bool(0.5) {
+        rng.gen_range(min - 10..min)
+    } else {
+        rng.gen_range(max + 1..=max + 10)
+    }
+}
+
+/// A value for `key`, occasionally one that fails part two's checks.
+fn day4_field(rng: &mut impl Rng, key: &str) -> String {
+    let valid = rng.gen_bool(0.9);
+    match key {
+        "byr" => day4_year(rng, valid, 1920, 2002).to_string(),
+        "iyr" => day4_year(rng, valid, 2010, 2020).to_string(),
+        "eyr" => day4_year(rng, valid, 2020, 2030).to_string(),
+        "hgt" => match (valid, rng.gen_bool(0.5)) {
+            (true, true) => format!("{}cm", rng.gen_range(150..=193)),
+            (true, false) => format!("{}in", rng.gen_range(59..=76)),
+            (false, true) => format!("{}cm", rng.gen_range(194..=250)),
+            (false, false) => rng.gen_range(59..=193).to_string(),
+        },
+        "hcl" if valid => format!("#{:06x}", rng.gen_range(0..0x100_0000)),
+        "hcl" => format!("{:06x}", rng.gen_range(0..0x100_0000)),
+        "ecl" if valid => ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"]
+            .choose(rng)
+            .unwrap()
+            .to_string(),
+        "ecl" => ["xry", "zzz", "gmt", "utc"]
+            .choose(rng)
+            .unwrap()
+            .to_string(),
+        "pid" if valid => format!("{:09}", rng.gen_range(0..1_000_000_000)),
+        "pid" => rng.gen_range(0..100_000_000).to_string(),
+        _ => rng.gen_range(100..350).to_string(),
+    }
+}
+
+fn day4(rng: &mut impl Rng, size: usize) -> String {
+    let keys = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid", "cid"];
+    let passports = (0..size).map(|_| {
+        let mut fields = Vec::new();
+        for key in &keys {
+            if rng.gen_bool(0.95) {
+                fields.push(format!("{}:{}", key, day4_field(rng, key)));
+            }
+        }
+        fields.shuffle(rng);
+
+        let mut passport = String::new();
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                passport.push(if rng.gen_bool(0.3) { '\n' } else { ' ' });
+            }
+            passport.push_str(field);
+        }
+        passport
+    });
+
+    passports
+        .filter(|passport| !passport.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn day5(rng: &mut impl Rng, size: usize) -> String {
+    let size = size.clamp(2, 1000) as u16;
+    let first = rng.gen_range(0..=1023 - size);
+    let missing = rng.gen_range(first + 1..first + size);
+    let mut ids = (first..=first + size)
+        .filter(|&id| id != missing)
+        .collect::<Vec<_>>();
+    ids.shuffle(rng);
+
+    let passes = ids.iter().map(|id| {
+        format!("{:010b}", id)
+            .chars()
+            .enumerate()
+            .map(|(i, bit)| match (i < 7, bit) {
+                (true, '0') => 'F',
+                (true, _) => 'B',
+                (false, '0') => 'L',
+                (false, _) => 'R',
+            })
+            .collect::<String>()
+    });
+
+    lines(passes)
+}
+
+fn day6(rng: &mut impl Rng, size: usize) -> String {
+    let groups = (0..size).map(|_| {
+        let shared = (0..rng.gen_range(0..=5))
+            .map(|_| lowercase(rng))
+            .collect::<Vec<_>>();
+        let people = (0..rng.gen_range(1..=5)).map(|_| {
+            let mut answers = shared.clone();
+            answers.extend((0..rng.gen_range(0..=4)).map(|_| lowercase(rng)));
+            if answers.is_empty() {
+                answers.push(lowercase(rng));
+            }
+            answers.sort_unstable();
+            answers.dedup();
+            answers.shuffle(rng);
+            answers.into_iter().collect::<String>()
+        });
+        lines(people)
+    });
+
+    groups.collect::<Vec<_>>().join("\n\n")
+}
+
+fn day7(rng: &mut impl Rng, size: usize) -> String {
+    const ADJECTIVES: &[&str] = &[
+        "bright", "clear", "dark", "dim", "dotted", "drab", "dull", "faded", "light", "mirrored",
+        "muted", "pale", "plaid", "posh", "shiny", "striped", "vibrant", "wavy",
+    ];
+    const COLORS: &[&str] = &[
+        "aqua",
+        "beige",
+        "black",
+        "blue",
+        "bronze",
+        "brown",
+        "chartreuse",
+        "coral",
+        "crimson",
+        "cyan",
+        "fuchsia",
+        "gold",
+        "gray",
+        "green",
+        "indigo",
+        "lavender",
+        "lime",
+        "magenta",
+        "maroon",
+        "olive",
+        "orange",
+        "plum",
+        "purple",
+        "red",
+        "salmon",
+        "silver",
+        "tan",
+        "teal",
+        "tomato",
+        "turquoise",
+        "violet",
+        "white",
+        "yellow",
+    ];
+    // Caps how many bags any one bag holds in total, which also bounds the
+    // number of paths the solvers walk.
+    const MAX_CONTENTS: usize = 10_000;
+
+    let mut names = ADJECTIVES
+        .iter()
+        .flat_map(|adjective| {
+            COLORS
+                .iter()
+                .map(move |color| format!("{} {}", adjective, color))
+        })
+        .filter(|name| name != "shiny gold")
+        .collect::<Vec<_>>();
+    names.shuffle(rng);
+    names.truncate(size.max(1) - 1);
+    let position = rng.gen_range(0..=names.len());
+    names.insert(position, "shiny gold".to_string());
+
+    // Bags only hold bags later in the list, so the rules form a DAG.
+    let mut contents = vec![0; names.len()];
+    let mut rules = vec![String::new(); names.len()];
+    for i in (0..names.len()).rev() {
+        let mut held = Vec::new();
+        let later = names.len() - i - 1;
+        let children = rng.gen_range(0..=later.min(4));
+        for j in index::sample(rng, later, children)
+            .into_iter()
+            .map(|j| i + 1 + j)
+        {
+            let count = rng.gen_range(1..=5);
+            let total = contents[i] + count * (1 + contents[j]);
+            if total <= MAX_CONTENTS {
+                contents[i] = total;
+                let plural = if count == 1 { "" } else { "s" };
+                held.push(format!("{} {} bag{}", count, names[j], plural));
+            }
+        }
+
+        rules[i] = if held.is_empty() {
+            format!("{} bags contain no other bags.", names[i])
+        } else {
+            format!("{} bags contain {}.", names[i], held.join(", "))
+        };
+    }
+
+    rules.shuffle(rng);
+    lines(rules)
+}
+
+fn day8(rng: &mut impl Rng, size: usize) -> String {
+    let size = size.clamp(1, 30_000) as i64;
+    // Running the first `corrupt` instructions always reaches the jump back
+    // to the start, so flipping that jump is the one repair that lets the
+    // forward-only instructions after it run off the end.
+    let corrupt = rng.gen_range(0..size);
+    let program = (0..size).map(|i| {
+        let end = if i < corrupt { corrupt } else { size };
+        let (instruction, argument) = if i == corrupt {
+            ("jmp", -i)
+        } else {
+            match rng.gen_range(0..3) {
+                0 => ("acc", rng.gen_range(-50..=50)),
+                1 if i < corrupt => ("nop", rng.gen_range(-i..=end - i)),
+                1 => ("nop", rng.gen_range(-100..=100)),
+                _ => ("jmp", rng.gen_range(1..=(end - i).min(10))),
+            }
+        };
+        format!("{} {:+}", instruction, argument)
+    });
+
+    lines(program)
+}
+
+fn day9(rng: &mut impl Rng, size: usize) -> String {
+    const PREAMBLE: usize = 25;
+    let size = size.clamp(PREAMBLE + 2, 1000);
+
+    let mut numbers = index::sample(rng, 100, PREAMBLE)
+        .into_iter()
+        .map(|n| n as u64 + 1)
+        .collect::<Vec<_>>();
+    while numbers.len() < size - 1 {
+        // Summing two of the smallest keeps growth to roughly doubling
+        // every preamble, well within `u64` at the size limit.
+        let mut window = numbers[numbers.len() - PREAMBLE..].to_vec();
+        window.sort_unstable();
+        let picks = index::sample(rng, 5, 2);
+        numbers.push(window[picks.index(0)] + window[picks.index(1)]);
+    }
+
+    let window = &numbers[numbers.len() - PREAMBLE..];
+    let invalid = loop {
+        let length = rng.gen_range(2..=17);
+        let start = rng.gen_range(0..numbers.len() - length);
+        let sum = numbers[start..start + length].iter().sum::<u64>();
+        let paired = window
+            .iter()
+            .enumerate()
+            .any(|(i, &a)| window[i + 1..].iter().any(|&b| a + b == sum));
+        if !paired {
+            break sum;
+        }
+    };
+    numbers.push(invalid);
+
+    lines(numbers.iter().map(u64::to_string))
+}
+
+fn day10(rng: &mut impl Rng, size: usize) -> String {
+    // Arrangements of a run of one jolt steps, by the length of the run.
+    const ARRANGEMENTS: [u64; 5] = [1, 1, 2, 4, 7];
+    const MAX_ARRANGEMENTS: u64 = 1 << 60;
+
+    let mut adapters = Vec::with_capacity(size);
+    let mut joltage = 0;
+    let mut arrangements = 1u64;
+    while adapters.len() < size {
+        let run = (0..=rng.gen_range(0..=4))
+            .rev()
+            .find(|&run| arrangements * ARRANGEMENTS[run] <= MAX_ARRANGEMENTS)
+            .unwrap_or(0);
+        arrangements *= ARRANGEMENTS[run];
+        for _ in 0..run {
+            joltage += 1;
+            adapters.push(joltage);
+        }
+        joltage += 3;
+        adapters.push(joltage);
+    }
+
+    adapters.truncate(size);
+    adapters.shuffle(rng);
+    lines(adapters.iter().map(usize::to_string))
+}
+
+/// Runs day 11's rules until the seats settle, returning the seats that
+/// keep changing if they fall into a cycle instead.
+fn day11_unsettled(
+    seats: &[Vec<char>],
+    tolerance: usize,
+    line_of_sight: bool,
+) -> Vec<(usize, usize)> {
+    let (width, height) = (seats[0].len() as i64, seats.len() as i64);
+    let occupied = |state: &[Vec<char>], x: i64, y: i64| {
+        let mut count = 0;
+        for (dx, dy) in (-1..=1).cartesian_product(-1..=1) {
+            let (mut nx, mut ny) = (x + dx, y + dy);
+            while (dx, dy) != (0, 0) && (0..width).contains(&nx) && (0..height).contains(&ny) {
+                match state[ny as usize][nx as usize] {
+                    '#' => count += 1,
+                    '.' if line_of_sight => {
+                        nx += dx;
+                        ny += dy;
+                        continue;
+                    }
+                    _ => {}
+                }
+                break;
+            }
+        }
+        count
+    };
+
+    let mut seen = HashSet::new();
+    let mut state = seats.to_vec();
+    loop {
+        let mut next = state.clone();
+        let mut changed = Vec::new();
+        for (y, x) in (0..height).cartesian_product(0..width) {
+            let seat = &mut next[y as usize][x as usize];
+            match (*seat, occupied(&state, x, y)) {
+                ('L', 0) => *seat = '#',
+                ('#', count) if count >= tolerance => *seat = 'L',
+                _ => continue,
+            }
+            changed.push((x as usize, y as usize));
+        }
+
+        if changed.is_empty() || !seen.insert(state) {
+            return changed;
+        }
+        state = next;
+    }
+}
+
+/// Random layouts can blink forever, so seats that never settle under
+/// either part's rules are swapped for floor until every seat does.
+fn day11(rng: &mut impl Rng, size: usize) -> String {
+    let mut seats = cells(rng, size, size, &[('L', 6), ('.', 1)]);
+    loop {
+        let mut unsettled = day11_unsettled(&seats, 4, false);
+        if unsettled.is_empty() {
+            unsettled = day11_unsettled(&seats, 5, true);
+        }
+        if unsettled.is_empty() {
+            break;
+        }
+        for (x, y) in unsettled {
+            seats[y][x] = '.';
+        }
+    }
+
+    lines(seats.iter().map(|row| row.iter().collect::<String>()))
+}
+
+fn day12(rng: &mut impl Rng, size: usize) -> String {
+    let instructions = (0..size).map(|_| {
+        let action = *b"NSEWLRF".choose(rng).unwrap() as char;
+        let argument = match action {
+            'L' | 'R' => 90 * rng.gen_range(1..=3),
+            _ => rng.gen_range(1..=100),
+        };
+        format!("{}{}", action, argument)
+    });
+
+    lines(instructions)
+}
+
+fn day13(rng: &mut impl Rng, size: usize) -> String {
+    // Keeps the intermediate products of the remainder theorem within `i64`.
+    const MAX_PRODUCT: i64 = 100_000_000_000_000;
+
+    let mut primes = (7..1000i64)
+        .filter(|&n| (2..n).take_while(|d| d * d <= n).all(|d| n % d != 0))
+        .collect::<Vec<_>>();
+    primes.shuffle(rng);
+
+    let mut product = 1;
+    let mut schedule = Vec::new();
+    for bus in primes.into_iter().take(size.max(1)) {
+        if product * bus > MAX_PRODUCT {
+            continue;
+        }
+
+        // Offsets stay below the bus ID so every remainder is positive.
+        let free = (0..bus.min(100) as usize)
+            .filter(|&offset| !matches!(schedule.get(offset), Some(Some(_))))
+            .collect::<Vec<_>>();
+        if let Some(&offset) = free.choose(rng) {
+            if schedule.len() <= offset {
+                schedule.resize(offset + 1, None);
+            }
+            schedule[offset] = Some(bus);
+            product *= bus;
+        }
+    }
+
+    let schedule = schedule
+        .iter()
+        .map(|bus| bus.map_or("x".to_string(), |bus| bus.to_string()))
+        .collect::<Vec<_>>();
+    lines(&[
+        rng.gen_range(100_000..1_000_000).to_string(),
+        schedule.join(","),
+    ])
+}
+
+fn day14(rng: &mut impl Rng, size: usize) -> String {
+    let mut program = Vec::new();
+    for _ in 0..size {
+        // Floating bits multiply the writes in part two, so keep them few.
+        let count = rng.gen_range(0..=9);
+        let floating = index::sample(rng, 36, count).into_vec();
+        let mask = (0..36)
+            .map(|i| match (floating.contains(&i), rng.gen_bool(0.5)) {
+                (true, _) => 'X',
+                (false, true) => '1',
+                (false, false) => '0',
+            })
+            .collect::<String>();
+        program.push(format!("mask = {}", mask));
+        for _ in 0..rng.gen_range(1..=6) {
+            let address = rng.gen_range(0..1 << 16);
+            let value = rng.gen_range(0..1u64 << 36);
+            program.push(format!("mem[{}] = {}", address, value));
+        }
+    }
+
+    lines(program)
+}
+
+fn day15(rng: &mut impl Rng, size: usize) -> String {
+    let size = size.clamp(1, 1000);
+    let numbers = index::sample(rng, (size * 4).max(20), size)
+        .into_iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>();
+
+    numbers.join(",")
+}
+
+fn day16<R: Rng>(rng: &mut R, size: usize) -> String {
+    const NAMES: &[&str] = &[
+        "departure location",
+        "departure station",
+        "departure platform",
+        "departure track",
+        "departure date",
+        "departure time",
+        "arrival location",
+        "arrival station",
+        "arrival platform",
+        "arrival track",
+        "class",
+        "duration",
+        "price",
+        "route",
+        "row",
+        "seat",
+        "train",
+        "type",
+        "wagon",
+        "zone",
+    ];
+    let count = NAMES.len();
+    let (low, high) = (rng.gen_range(25..50), rng.gen_range(950..975));
+
+    // Each rule covers `low..=high` except for one hole. A field rules out
+    // exactly the rules ranked above it by holding their holes, which makes
+    // the candidates for each field a strict superset of the previous one.
+    let holes = index::sample(rng, (high - low - 1) as usize, count)
+        .into_iter()
+        .map(|h| h as u64 + low + 1)
+        .collect::<Vec<_>>();
+    let mut rank = (0..count).collect::<Vec<_>>();
+    rank.shuffle(rng);
+    let mut rule_of_field = (0..count).collect::<Vec<_>>();
+    rule_of_field.shuffle(rng);
+
+    let value = |rng: &mut R| loop {
+        let value = rng.gen_range(low..=high);
+        if !holes.contains(&value) {
+            break value;
+        }
+    };
+
+    let valid = size.max(25) * 4 / 5;
+    let mut tickets = vec![vec![0; count]; size.max(25)];
+    for (field, &rule) in rule_of_field.iter().enumerate() {
+        let mut excluded = (0..count)
+            .filter(|&other| rank[other] > rank[rule])
+            .map(|other| holes[other])
+            .collect::<Vec<_>>();
+        excluded.resize_with(valid, || value(rng));
+        excluded.shuffle(rng);
+        for (ticket, value) in tickets.iter_mut().zip(excluded) {
+            ticket[field] = value;
+        }
+    }
+    for ticket in &mut tickets[valid..] {
+        for field in ticket.iter_mut() {
+            *field = value(rng);
+        }
+        let field = rng.gen_range(0..count);
+        ticket[field] = if rng.gen_bool(0.5) {
+            rng.gen_range(0..low)
+        } else {
+            rng.gen_range(high + 1..1000)
+        };
+    }
+    tickets.shuffle(rng);
+
+    let ticket = |values: &[u64]| {
+        values
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    let rules = NAMES
+        .iter()
+        .zip(&holes)
+        .map(|(name, hole)| format!("{}: {}-{} or {}-{}", name, low, hole - 1, hole + 1, high));
+    let mine = (0..count).map(|_| value(rng)).collect::<Vec<_>>();
+
+    let mut input = lines(rules);
+    input.push_str("\n\nyour ticket:\n");
+    input.push_str(&ticket(&mine));
+    input.push_str("\n\nnearby tickets:\n");
+    input.push_str(&lines(tickets.iter().map(|values| ticket(values))));
+    input
+}
+
+/// An expression of at most `budget` digits, which must be at least two.
+/// Every value stays below the product of one more than each digit, however
+/// the operators are ranked, so a small budget rules out overflow.
+fn day18_expression(rng: &mut impl Rng, budget: &mut usize, depth: usize) -> String {
+    let terms = rng.gen_range(2..=4).min(*budget);
+    let mut expression = String::new();
+    for i in 0..terms {
+        if i > 0 {
+            expression.push_str(if rng.gen_bool(0.5) { " + " } else { " * " });
+        }
+
+        // Leave a digit for each of the terms still to come.
+        let available = *budget - (terms - i - 1);
+        if depth < 2 && available >= 2 && rng.gen_bool(0.3) {
+            let mut inner = available;
+            expression.push('(');
+            expression.push_str(&day18_expression(rng, &mut inner, depth + 1));
+            expression.push(')');
+            *budget -= available - inner;
+        } else {
+            *budget -= 1;
+            expression.push_str(&rng.gen_range(1..=9).to_string());
+        }
+    }
+
+    expression
+}
+
+fn day18(rng: &mut impl Rng, size: usize) -> String {
+    let expressions = (0..size).map(|_| day18_expression(rng, &mut 12, 0));
+    lines(expressions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::{self, Part};
+
+    #[test]
+    fn inputs_are_deterministic() {
+        for day in registry::days(2020) {
+            assert_eq!(
+                input(2020, day, 30, 7),
+                input(2020, day, 30, 7),
+                "day {}",
+                day
+            );
+        }
+        assert_ne!(input(2020, 1, 30, 1), input(2020, 1, 30, 2));
+        assert_eq!(input(2020, 19, 30, 1), None);
+        assert_eq!(input(2019, 1, 30, 1), None);
+    }
+
+    #[test]
+    fn inputs_parse_and_solve() {
+        for solver in registry::solvers(2020) {
+            for seed in 0..3 {
+                let size = if solver.day() == 17 { 3 } else { 30 };
+                let input = input(solver.year(), solver.day(), size, seed).unwrap();
+                let parsed = solver
+                    .parse(&input)
+                    .unwrap_or_else(|e| panic!("seed {}: {}", seed, e));
+                for &part in solver.parts() {
+                    // Thirty million rounds take too long for a debug build.
+                    if (solver.day(), part) != (15, Part::Two) {
+                        solver.solve(&parsed, part);
+                    }
+                }
+            }
+        }
+    }
+}
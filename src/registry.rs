@@ -0,0 +1,187 @@
+use std::{any::Any, fmt, io::BufRead, marker::PhantomData};
+
+use crate::{ParseError, Puzzle};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub fn number(self) -> u8 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+
+    pub fn from_number(number: u8) -> Option<Part> {
+        match number {
+            1 => Some(Part::One),
+            2 => Some(Part::Two),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+const PARTS: &[Part] = &[Part::One, Part::Two];
+
+/// Puzzle input that has been parsed by a [`Solver`], ready to be solved.
+pub struct Parsed {
+    year: u16,
+    day: u8,
+    input: Box<dyn Any + Send + Sync>,
+}
+
+/// A [`Puzzle`] with its types erased so that days can be listed and
+/// dispatched by number at runtime.
+pub trait Solver: Sync {
+    fn year(&self) -> u16;
+    fn day(&self) -> u8;
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError>;
+
+    /// Parses input as it is read, see [`Puzzle::parse_reader`].
+    fn parse_reader(&self, reader: &mut dyn BufRead) -> Result<Parsed, ParseError>;
+
+    /// Solves one part of already parsed input.
+    ///
+    /// Panics if `input` was parsed by a different day.
+    fn solve(&self, input: &Parsed, part: Part) -> String;
+
+    fn parts(&self) -> &'static [Part] {
+        PARTS
+    }
+
+    fn run(&self, input: &str, part: Part) -> Result<String, ParseError> {
+        let parsed = self.parse(input)?;
+        Ok(self.solve(&parsed, part))
+    }
+}
+
+struct Entry<P>(PhantomData<fn() -> P>);
+
+impl<P> Solver for Entry<P>
+where
+    P: Puzzle,
+    P::Input: Send + Sync + 'static,
+{
+    fn year(&self) -> u16 {
+        P::YEAR
+    }
+
+    fn day(&self) -> u8 {
+        P::DAY
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        let input = P::parse(input)?;
+        Ok(Parsed {
+            year: P::YEAR,
+            day: P::DAY,
+            input: Box::new(input),
+        })
+    }
+
+    fn parse_reader(&self, reader: &mut dyn BufRead) -> Result<Parsed, ParseError> {
+        let input = P::parse_reader(reader)?;
+        Ok(Parsed {
+            year: P::YEAR,
+            day: P::DAY,
+            input: Box::new(input),
+        })
+    }
+
+    fn solve(&self, parsed: &Parsed, part: Part) -> String {
+        let input = parsed.input.downcast_ref::<P::Input>().unwrap_or_else(|| {
+            panic!(
+                "{} day {} cannot solve input for {} day {}",
+                P::YEAR,
+                P::DAY,
+                parsed.year,
+                parsed.day
+            )
+        });
+        match part {
+            Part::One => P::part1(input).to_string(),
+            Part::Two => P::part2(input).to_string(),
+        }
+    }
+}
+
+static YEAR_2020: &[&dyn Solver] = &[
+    #[cfg(feature = "day1")]
+    &Entry::<crate::day1::Day1>(PhantomData),
+    #[cfg(feature = "day2")]
+    &Entry::<crate::day2::Day2>(PhantomData),
+    #[cfg(feature = "day3")]
+    &Entry::<crate::day3::Day3>(PhantomData),
+    #[cfg(feature = "day4")]
+    &Entry::<crate::day4::Day4>(PhantomData),
+    #[cfg(feature = "day5")]
+    &Entry::<crate::day5::Day5>(PhantomData),
+    #[cfg(feature = "day6")]
+    &Entry::<crate::day6::Day6>(PhantomData),
+    #[cfg(feature = "day7")]
+    &Entry::<crate::day7::Day7>(PhantomData),
+    #[cfg(feature = "day8")]
+    &Entry::<crate::day8::Day8>(PhantomData),
+    #[cfg(feature = "day9")]
+    &Entry::<crate::day9::Day9>(PhantomData),
+    #[cfg(feature = "day10")]
+    &Entry::<crate::day10::Day10>(PhantomData),
+    #[cfg(feature = "day11")]
+    &Entry::<crate::day11::Day11>(PhantomData),
+    #[cfg(feature = "day12")]
+    &Entry::<crate::day12::Day12>(PhantomData),
+    #[cfg(feature = "day13")]
+    &Entry::<crate::day13::Day13>(PhantomData),
+    #[cfg(feature = "day14")]
+    &Entry::<crate::day14::Day14>(PhantomData),
+    #[cfg(feature = "day15")]
+    &Entry::<crate::day15::Day15>(PhantomData),
+    #[cfg(feature = "day16")]
+    &Entry::<crate::day16::Day16>(PhantomData),
+    #[cfg(feature = "day17")]
+    &Entry::<crate::day17::Day17>(PhantomData),
+    #[cfg(feature = "day18")]
+    &Entry::<crate::day18::Day18>(PhantomData),
+];
+
+/// The days of each year, oldest year first.
+static YEARS: &[(u16, &[&dyn Solver])] = &[(2020, YEAR_2020)];
+
+/// Every year with at least one day available, oldest first.
+pub fn years() -> impl Iterator<Item = u16> {
+    YEARS.iter().map(|&(year, _)| year)
+}
+
+/// The most recent year, used when no year is asked for.
+pub fn latest_year() -> u16 {
+    YEARS.last().map(|&(year, _)| year).unwrap()
+}
+
+/// Every available day of `year`, in order.
+pub fn solvers(year: u16) -> &'static [&'static dyn Solver] {
+    YEARS
+        .iter()
+        .find(|&&(y, _)| y == year)
+        .map_or(&[], |&(_, solvers)| solvers)
+}
+
+pub fn days(year: u16) -> impl Iterator<Item = u8> {
+    solvers(year).iter().map(|solver| solver.day())
+}
+
+pub fn solver(year: u16, day: u8) -> Option<&'static dyn Solver> {
+    solvers(year)
+        .iter()
+        .copied()
+        .find(|solver| solver.day() == day)
+}
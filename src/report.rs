@@ -0,0 +1,288 @@
+use std::{
+    io::{self, BufRead, Write},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{
+    memory,
+    registry::{Parsed, Part, Solver},
+    ParseError,
+};
+
+/// The answer to one part of a day along with what it cost to produce.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub year: u16,
+    pub day: u8,
+    pub part: Part,
+    pub answer: String,
+    pub parse_time: Duration,
+    pub solve_time: Duration,
+    /// Most bytes allocated at once while parsing and solving this part, if
+    /// [`memory::PeakAllocator`] is installed and nothing else was running
+    /// at the same time.
+    pub peak_allocation: Option<usize>,
+}
+
+/// Parses `input` once and solves each of `parts`, timing the generator
+/// separately from the solvers.
+pub fn measure(
+    solver: &dyn Solver,
+    input: &str,
+    parts: &[Part],
+) -> Result<Vec<Record>, ParseError> {
+    measure_with(solver, parts, || solver.parse(input))
+}
+
+/// Like [`measure`], parsing the input as it is read from `reader`, so the
+/// parse time includes reading it.
+pub fn measure_reader(
+    solver: &dyn Solver,
+    reader: &mut dyn BufRead,
+    parts: &[Part],
+) -> Result<Vec<Record>, ParseError> {
+    measure_with(solver, parts, || solver.parse_reader(reader))
+}
+
+fn measure_with<F>(solver: &dyn Solver, parts: &[Part], parse: F) -> Result<Vec<Record>, ParseError>
+where
+    F: FnOnce() -> Result<Parsed, ParseError>,
+{
+    let baseline = memory::current();
+    memory::reset_peak();
+    let start = Instant::now();
+    let parsed = parse()?;
+    let parse_time = start.elapsed();
+    let parse_peak = memory::peak().saturating_sub(baseline);
+
+    let records = parts
+        .iter()
+        .map(|&part| {
+            memory::reset_peak();
+            let start = Instant::now();
+            let answer = solver.solve(&parsed, part);
+            let solve_time = start.elapsed();
+            let solve_peak = memory::peak().saturating_sub(baseline);
+
+            Record {
+                year: solver.year(),
+                day: solver.day(),
+                part,
+                answer,
+                parse_time,
+                solve_time,
+                peak_allocation: memory::installed().then(|| parse_peak.max(solve_peak)),
+            }
+        })
+        .collect();
+
+    Ok(records)
+}
+
+/// Like [`measure`] for several days at once, running every day and part
+/// across the rayon thread pool. Results come back in the order of `days`.
+///
+/// The allocation peak is shared by everything running at the same time,
+/// so no record has one.
+#[cfg(feature = "rayon")]
+pub fn measure_all(
+    days: &[(&dyn Solver, String)],
+    parts: &[Part],
+) -> Vec<Result<Vec<Record>, ParseError>> {
+    days.par_iter()
+        .map(|&(solver, ref input)| {
+            let start = Instant::now();
+            let parsed = solver.parse(input)?;
+            let parse_time = start.elapsed();
+
+            let records = parts
+                .par_iter()
+                .map(|&part| {
+                    let start = Instant::now();
+                    let answer = solver.solve(&parsed, part);
+                    let solve_time = start.elapsed();
+
+                    Record {
+                        year: solver.year(),
+                        day: solver.day(),
+                        part,
+                        answer,
+                        parse_time,
+                        solve_time,
+                        peak_allocation: None,
+                    }
+                })
+                .collect();
+
+            Ok(records)
+        })
+        .collect()
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("unknown report format `{}`", s)),
+        }
+    }
+}
+
+/// Writes records one per line, as plain text, JSON Lines or CSV.
+pub struct Report<W> {
+    out: W,
+    format: Format,
+}
+
+impl<W: Write> Report<W> {
+    pub fn new(mut out: W, format: Format) -> io::Result<Self> {
+        if format == Format::Csv {
+            writeln!(out, "year,day,part,answer,parse_ns,solve_ns,peak_bytes")?;
+        }
+
+        Ok(Report { out, format })
+    }
+
+    pub fn write(&mut self, record: &Record) -> io::Result<()> {
+        let Record {
+            year,
+            day,
+            part,
+            answer,
+            parse_time,
+            solve_time,
+            peak_allocation,
+        } = record;
+
+        match self.format {
+            Format::Text => {
+                write!(
+                    self.out,
+                    "{} Day {:>2} - Part {}: {:<20} (parse {:?}, solve {:?}",
+                    year, day, part, answer, parse_time, solve_time
+                )?;
+                match peak_allocation {
+                    Some(peak) => writeln!(self.out, ", peak {} bytes)", peak),
+                    None => writeln!(self.out, ")"),
+                }
+            }
+            Format::Json => writeln!(
+                self.out,
+                r#"{{"year":{},"day":{},"part":{},"answer":{},"parse_ns":{},"solve_ns":{},"peak_bytes":{}}}"#,
+                year,
+                day,
+                part,
+                json_string(answer),
+                parse_time.as_nanos(),
+                solve_time.as_nanos(),
+                peak_allocation.map_or("null".to_string(), |peak| peak.to_string())
+            ),
+            Format::Csv => writeln!(
+                self.out,
+                "{},{},{},{},{},{},{}",
+                year,
+                day,
+                part,
+                csv_field(answer),
+                parse_time.as_nanos(),
+                solve_time.as_nanos(),
+                peak_allocation.map_or(String::new(), |peak| peak.to_string())
+            ),
+        }
+    }
+}
+
+pub(crate) fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(all(test, feature = "day1"))]
+mod tests {
+    use super::*;
+    use crate::registry;
+
+    #[test]
+    fn no_peak_without_the_allocator() {
+        let solver = registry::solver(2020, 1).unwrap();
+        let records = measure(solver, "1721\n979\n366\n299\n675\n1456", &[Part::One]).unwrap();
+        assert_eq!(records[0].peak_allocation, None);
+    }
+
+    #[cfg(all(feature = "rayon", feature = "day2", feature = "day8"))]
+    #[test]
+    fn measure_all_keeps_order() {
+        let days = [
+            (
+                registry::solver(2020, 2).unwrap(),
+                "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc".to_string(),
+            ),
+            (
+                registry::solver(2020, 1).unwrap(),
+                "1721\n979\n366\n299\n675\n1456".to_string(),
+            ),
+            (registry::solver(2020, 8).unwrap(), "bogus +1".to_string()),
+        ];
+        let results = measure_all(&days, &[Part::One, Part::Two]);
+
+        let answers = |result: &Result<Vec<Record>, ParseError>| {
+            result.as_ref().ok().map(|records| {
+                records
+                    .iter()
+                    .map(|record| (record.day, record.part, record.answer.clone()))
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(
+            answers(&results[0]),
+            Some(vec![
+                (2, Part::One, "2".to_string()),
+                (2, Part::Two, "1".to_string())
+            ])
+        );
+        assert_eq!(
+            answers(&results[1]),
+            Some(vec![
+                (1, Part::One, "514579".to_string()),
+                (1, Part::Two, "241861950".to_string())
+            ])
+        );
+        assert!(results[2].is_err());
+    }
+}
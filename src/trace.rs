@@ -0,0 +1,178 @@
+//! Structured events that explain how a solver reached its answer.
+//!
+//! Days report intermediate steps with [`event!`], which costs a single
+//! atomic load unless a caller is collecting events with [`capture`]. The
+//! collector is shared by the whole process, so events from solvers that
+//! use several threads are kept, but only one capture should run at once.
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use crate::{registry::Part, report::json_string};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static EVENTS: Mutex<Vec<Event>> = Mutex::new(Vec::new());
+
+/// The value of one named field of an [`Event`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Number(i128),
+    Text(String),
+}
+
+macro_rules! numbers {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Value {
+                fn from(value: $t) -> Self {
+                    Value::Number(value as i128)
+                }
+            }
+        )*
+    };
+}
+
+numbers!(i16, i32, i64, u8, u16, u32, u64, usize);
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Text(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Text(value)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// One step taken by a day's solver, such as an instruction executed or a
+/// decision made, with the values that explain it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub day: u8,
+    pub name: &'static str,
+    pub fields: Vec<(&'static str, Value)>,
+}
+
+impl Event {
+    /// Formats the event as a JSON object, tagged with the year and part it
+    /// belongs to.
+    pub fn to_json(&self, year: u16, part: Part) -> String {
+        let mut json = format!(
+            r#"{{"year":{},"day":{},"part":{},"event":{}"#,
+            year,
+            self.day,
+            part,
+            json_string(self.name)
+        );
+        for (key, value) in &self.fields {
+            let value = match value {
+                Value::Number(n) => n.to_string(),
+                Value::Text(s) => json_string(s),
+            };
+            json.push_str(&format!(",{}:{}", json_string(key), value));
+        }
+        json.push('}');
+        json
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        for (key, value) in &self.fields {
+            write!(f, " {}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether anyone is collecting events. Checked by [`event!`] before it
+/// builds an event.
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn emit(event: Event) {
+    if enabled() {
+        EVENTS.lock().unwrap().push(event);
+    }
+}
+
+/// Runs `f` and returns what it produced along with every event emitted
+/// while it ran, in the order they were emitted.
+pub fn capture<R, F>(f: F) -> (R, Vec<Event>)
+where
+    F: FnOnce() -> R,
+{
+    EVENTS.lock().unwrap().clear();
+    ENABLED.store(true, Ordering::Relaxed);
+    let result = f();
+    ENABLED.store(false, Ordering::Relaxed);
+
+    let events = EVENTS.lock().unwrap().drain(..).collect();
+    (result, events)
+}
+
+/// Emits an event for `day` named `name` with `key = value` fields, where
+/// every value converts into a [`Value`]. Nothing is evaluated unless events
+/// are being captured.
+macro_rules! event {
+    ($day:expr, $name:expr $(, $key:ident = $value:expr)* $(,)?) => {
+        if $crate::trace::enabled() {
+            $crate::trace::emit($crate::trace::Event {
+                day: $day,
+                name: $name,
+                fields: vec![$((stringify!($key), $crate::trace::Value::from($value))),*],
+            });
+        }
+    };
+}
+
+pub(crate) use event;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Other tests may be solving real days while this one captures, so
+    /// only look at events from the made up day 0.
+    fn day0(events: Vec<Event>) -> Vec<Event> {
+        events.into_iter().filter(|event| event.day == 0).collect()
+    }
+
+    #[test]
+    fn events_are_only_kept_while_capturing() {
+        event!(0, "ignored", value = 1);
+
+        let (answer, events) = capture(|| {
+            event!(0, "step", index = 2usize, op = "nop");
+            42
+        });
+        let events = day0(events);
+        assert_eq!(answer, 42);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].to_string(), "step index=2 op=nop");
+        assert_eq!(
+            events[0].to_json(2020, Part::Two),
+            r#"{"year":2020,"day":0,"part":2,"event":"step","index":2,"op":"nop"}"#
+        );
+
+        event!(0, "ignored", value = 1);
+        assert!(day0(capture(|| ()).1).is_empty());
+    }
+}
@@ -0,0 +1,121 @@
+use std::{fs, path::PathBuf};
+
+use advent_2020::{
+    registry::{self, Parsed, Solver},
+    ParseError,
+};
+
+const YEAR: u16 = 2020;
+
+fn input_dir(year: u16) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("input")
+        .join(year.to_string())
+}
+
+fn answers(year: u16) -> toml::Value {
+    fs::read_to_string(input_dir(year).join("answers.toml"))
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+fn check(day: u8) {
+    check_with(day, |solver, input| solver.parse(input));
+}
+
+fn check_with<F>(day: u8, parse: F)
+where
+    F: Fn(&dyn Solver, &str) -> Result<Parsed, ParseError>,
+{
+    let answers = answers(YEAR);
+    let expected = &answers[format!("day{}", day).as_str()];
+
+    let input = fs::read_to_string(input_dir(YEAR).join(format!("day{}.txt", day))).unwrap();
+    let solver = registry::solver(YEAR, day).unwrap();
+    let parsed = parse(solver, &input).unwrap();
+
+    for &part in solver.parts() {
+        let key = format!("part{}", part);
+        assert_eq!(
+            Some(solver.solve(&parsed, part).as_str()),
+            expected[key.as_str()].as_str(),
+            "day {} part {}",
+            day,
+            part
+        );
+    }
+}
+
+macro_rules! answers {
+    ($($name:ident => $day:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                check($day);
+            }
+        )*
+    };
+}
+
+answers! {
+    day1 => 1,
+    day2 => 2,
+    day3 => 3,
+    day4 => 4,
+    day5 => 5,
+    day6 => 6,
+    day7 => 7,
+    day8 => 8,
+    day9 => 9,
+    day10 => 10,
+    day11 => 11,
+    day12 => 12,
+    day13 => 13,
+    day14 => 14,
+    day15 => 15,
+    day16 => 16,
+    day17 => 17,
+    day18 => 18,
+}
+
+#[test]
+fn streamed_input() {
+    for &day in &[1, 2, 3, 8, 9, 10, 12, 14] {
+        check_with(day, |solver, input| {
+            solver.parse_reader(&mut input.as_bytes())
+        });
+    }
+}
+
+#[test]
+fn every_day_has_answers() {
+    for year in registry::years() {
+        let answers = answers(year);
+
+        for day in registry::days(year) {
+            let expected = answers.get(format!("day{}", day)).unwrap();
+            assert!(expected.get("part1").is_some() && expected.get("part2").is_some());
+        }
+    }
+}
+
+#[test]
+fn every_day_explains_its_answers() {
+    for solver in registry::solvers(YEAR) {
+        let day = solver.day();
+        let input = fs::read_to_string(input_dir(YEAR).join(format!("day{}.txt", day))).unwrap();
+        let parsed = solver.parse(&input).unwrap();
+        for &part in solver.parts() {
+            // Other tests may be solving while this captures, so only count
+            // events from the day being traced.
+            let (_, events) = advent_2020::trace::capture(|| solver.solve(&parsed, part));
+            assert!(
+                events.iter().any(|event| event.day == day),
+                "day {} part {}",
+                day,
+                part
+            );
+        }
+    }
+}
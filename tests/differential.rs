@@ -0,0 +1,50 @@
+use advent_2020::{
+    differential,
+    registry::{self, Part},
+};
+
+fn check(day: u8, max_size: usize) {
+    let solver = registry::solver(2020, day).unwrap();
+    // Thirty million rounds of day 15 are too slow to repeat here.
+    let parts = match day {
+        15 => &[Part::One][..],
+        _ => solver.parts(),
+    };
+
+    match differential::run(solver, parts, 1..=max_size, 0..4) {
+        Ok(compared) => assert!(compared > 0, "day {}: nothing to compare", day),
+        Err(mismatch) => panic!("{}", mismatch),
+    }
+}
+
+macro_rules! differential {
+    ($($name:ident => $day:expr, $max_size:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                check($day, $max_size);
+            }
+        )*
+    };
+}
+
+differential! {
+    day1 => 1, 12,
+    day2 => 2, 12,
+    day3 => 3, 12,
+    day4 => 4, 12,
+    day5 => 5, 12,
+    day6 => 6, 12,
+    day7 => 7, 12,
+    day8 => 8, 12,
+    day9 => 9, 30,
+    day10 => 10, 20,
+    day11 => 11, 8,
+    day12 => 12, 12,
+    day13 => 13, 12,
+    day14 => 14, 12,
+    day15 => 15, 12,
+    day16 => 16, 26,
+    day17 => 17, 3,
+    day18 => 18, 12,
+}
@@ -0,0 +1,72 @@
+use std::{fs, path::PathBuf};
+
+use advent_2020::{day12, day13, day14, day16, day17, day2, day4, day7, day8, Puzzle};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Parses the real input for `P`, sends it through JSON and back, and
+/// checks that the copy serializes the same and gives the same answers.
+fn round_trip<P>()
+where
+    P: Puzzle,
+    P::Input: Serialize + DeserializeOwned,
+{
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("input")
+        .join(P::YEAR.to_string())
+        .join(format!("day{}.txt", P::DAY));
+    let input = P::parse(&fs::read_to_string(path).unwrap()).unwrap();
+
+    let json = serde_json::to_string(&input).unwrap();
+    let copy = serde_json::from_str::<P::Input>(&json).unwrap();
+
+    // Maps come out in no particular order, so compare values, not text.
+    assert_eq!(
+        serde_json::to_value(&copy).unwrap(),
+        serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+        "day {}",
+        P::DAY
+    );
+    assert_eq!(P::part1(&copy).to_string(), P::part1(&input).to_string());
+    assert_eq!(P::part2(&copy).to_string(), P::part2(&input).to_string());
+}
+
+#[test]
+fn parsed_inputs_round_trip() {
+    round_trip::<day2::Day2>();
+    round_trip::<day4::Day4>();
+    round_trip::<day7::Day7>();
+    round_trip::<day8::Day8>();
+    round_trip::<day12::Day12>();
+    round_trip::<day13::Day13>();
+    round_trip::<day14::Day14>();
+    round_trip::<day16::Day16>();
+}
+
+#[test]
+fn points_serialize_as_tuples() {
+    let input = day17::Day17::parse(".#.\n..#\n###").unwrap();
+    let json = serde_json::to_value(&input).unwrap();
+    let cells = json["active_cells"].as_array().unwrap();
+    assert_eq!(cells.len(), 5);
+    assert!(cells.contains(&serde_json::json!([1, 0, 0, 0])));
+
+    let copy = serde_json::from_value::<<day17::Day17 as Puzzle>::Input>(json).unwrap();
+    assert_eq!(day17::Day17::part1(&copy), 112);
+}
+
+#[test]
+fn day14_instructions_are_checked() {
+    type Program = <day14::Day14 as Puzzle>::Input;
+    assert!(serde_json::from_str::<Program>(r#"["mask = 2"]"#).is_err());
+    assert!(serde_json::from_str::<Program>(r#"["mem[8] = 68719476736"]"#).is_err());
+
+    let program = serde_json::from_str::<Program>(r#"["mem[8] = 11"]"#).unwrap();
+    assert_eq!(day14::Day14::part1(&program), 11);
+}
+
+#[test]
+fn day14_writes_before_a_mask() {
+    type Program = <day14::Day14 as Puzzle>::Input;
+    let program = serde_json::from_str::<Program>(r#"["mem[8] = 11"]"#).unwrap();
+    assert_eq!(day14::Day14::part2(&program), 11);
+}